@@ -0,0 +1,79 @@
+/*
+ * Copyright 2022 sukawasatoru
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Builds tinytable-rs without the `std` feature.
+//!
+//! `cargo test --manifest-path no-std-check/Cargo.toml`
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use tinytable_rs::Attribute::*;
+use tinytable_rs::Type::*;
+use tinytable_rs::{column, unique, Column, Table};
+
+pub struct MyTable {
+    columns: Vec<Arc<Column>>,
+}
+
+impl MyTable {
+    pub fn new() -> Self {
+        let val = column("val", TEXT, [NOT_NULL]);
+        Self {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT]),
+                val.clone(),
+                unique([val]),
+            ],
+        }
+    }
+}
+
+impl Default for MyTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Table for MyTable {
+    fn name(&self) -> &str {
+        "my_table"
+    }
+
+    fn columns(&self) -> &[Arc<Column>] {
+        &self.columns
+    }
+}
+
+pub fn create_sql() -> String {
+    MyTable::new().create_sql()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn create_sql() {
+        assert_eq!(
+            super::create_sql(),
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY AUTOINCREMENT, val TEXT NOT NULL, UNIQUE (val))"
+        );
+    }
+}
@@ -14,7 +14,65 @@
  * limitations under the License.
  */
 
-use std::sync::Arc;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The builder functions, the `Table` trait and the variants of `Type`, `Attribute` and
+/// `ForeignKeyAttribute`.
+///
+/// ```
+/// use std::sync::Arc;
+/// use tinytable_rs::prelude::*;
+///
+/// struct MyTable {
+///     columns: Vec<Arc<Column>>,
+/// }
+///
+/// impl Table for MyTable {
+///     fn name(&self) -> &str {
+///         "my_table"
+///     }
+///
+///     fn columns(&self) -> &[Arc<Column>] {
+///         &self.columns
+///     }
+/// }
+///
+/// let val = column("val", TEXT, [NOT_NULL]);
+/// let table = MyTable {
+///     columns: vec![
+///         column("id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT]),
+///         val.clone(),
+///         unique([val]),
+///     ],
+/// };
+///
+/// let conn = rusqlite::Connection::open_in_memory().unwrap();
+/// conn.execute(&table.create_sql(), []).unwrap();
+/// ```
+pub mod prelude {
+    pub use crate::Attribute::{self, *};
+    pub use crate::ForeignKeyAttribute::{self, *};
+    pub use crate::Type::{self, *};
+    pub use crate::{
+        boolean_column, check, check_columns, check_glob, check_like, column, column_typed,
+        date_column, enum_column, foreign_key, foreign_key_with_actions, primary_key, references,
+        rowid_alias_column, try_foreign_key, unique, uuid_column, Column, DefaultValue,
+        GeneratedStorage, ReferentialAction, Table, Trigger,
+    };
+}
 
 pub fn column<T, A>(name: T, column_type: Type, attributes: A) -> Arc<Column>
 where
@@ -32,18 +90,113 @@ where
     })
 }
 
+/// A `BOOLEAN` column with `CHECK (name IN (0, 1))`, since SQLite itself accepts any value in a
+/// `BOOLEAN` column. Use `column` with `Type::BOOLEAN` for the unchecked form.
+pub fn boolean_column<T, A>(name: T, attributes: A) -> Arc<Column>
+where
+    T: Into<String>,
+    A: Into<Vec<Attribute>>,
+{
+    let name = name.into();
+    let mut attributes = attributes.into();
+    attributes.push(Attribute::CHECK(format!("{} IN (0, 1)", name)));
+    column(name, Type::BOOLEAN, attributes)
+}
+
+/// A `DATE` column with a `CHECK` that the value has the `YYYY-MM-DD` shape, since SQLite
+/// stores dates as text and accepts anything.
+///
+/// The check is a `GLOB` on the digits plus month and day ranges, so it is cheap but only
+/// rejects obviously wrong values such as `2020-13-40`; a date like `2020-02-31` passes.
+/// `NULL` passes as well unless `NOT_NULL` is given.
+pub fn date_column<T, A>(name: T, attributes: A) -> Arc<Column>
+where
+    T: Into<String>,
+    A: Into<Vec<Attribute>>,
+{
+    let name = name.into();
+    let mut attributes = attributes.into();
+    attributes.push(Attribute::CHECK(format!(
+        "{} GLOB '[0-9][0-9][0-9][0-9]-[0-1][0-9]-[0-3][0-9]' AND substr({}, 6, 2) BETWEEN '01' AND '12' AND substr({}, 9, 2) BETWEEN '01' AND '31'",
+        name, name, name
+    )));
+    column(name, Type::DATE, attributes)
+}
+
+/// A `TEXT` column with `CHECK (name IN ('a', 'b'))` limiting it to `allowed`.
+pub fn enum_column<T, A>(name: T, allowed: &[&str], attributes: A) -> Arc<Column>
+where
+    T: Into<String>,
+    A: Into<Vec<Attribute>>,
+{
+    let name = name.into();
+    let mut attributes = attributes.into();
+    attributes.push(Attribute::CHECK(format!(
+        "{} IN ({})",
+        name,
+        allowed
+            .iter()
+            .map(|data| escape_string(*data))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )));
+    column(name, Type::TEXT, attributes)
+}
+
+/// A `TEXT` column for UUIDs, as SQLite has no native UUID type. `strict` adds
+/// `CHECK (length(name) = 36)` to reject values that are not in the hyphenated form.
+pub fn uuid_column<T, A>(name: T, strict: bool, attributes: A) -> Arc<Column>
+where
+    T: Into<String>,
+    A: Into<Vec<Attribute>>,
+{
+    let name = name.into();
+    let mut attributes = attributes.into();
+    if strict {
+        attributes.push(Attribute::CHECK(format!("length({}) = 36", name)));
+    }
+    column(name, Type::TEXT, attributes)
+}
+
+/// `name INTEGER PRIMARY KEY`, which makes `name` an alias for the rowid so that
+/// `WHERE name = ?` is a direct rowid lookup. Unlike `AUTOINCREMENT`, the rowid of a deleted
+/// last row may be reused.
+pub fn rowid_alias_column<T: Into<String>>(name: T) -> Arc<Column> {
+    column(name, Type::INTEGER, [Attribute::PRIMARY_KEY])
+}
+
+/// A column with a literal declared type plus the affinity intended for introspection and
+/// code generation.
+pub fn column_typed<T, A>(
+    name: T,
+    declared: &str,
+    affinity_hint: Affinity,
+    attributes: A,
+) -> Arc<Column>
+where
+    T: Into<String>,
+    A: Into<Vec<Attribute>>,
+{
+    column(
+        name,
+        Type::Declared(declared.to_owned(), affinity_hint),
+        attributes,
+    )
+}
+
 pub fn primary_key<K: AsRef<[Arc<Column>]>>(keys: K) -> Arc<Column> {
-    Arc::new(Column::Constraint(format!(
-        "{} ({})",
-        Attribute::PRIMARY_KEY.name(),
+    Arc::new(Column::PrimaryKey(
         keys.as_ref()
             .iter()
             .map(|data| data.name().to_owned())
-            .collect::<Vec<_>>()
-            .join(", ")
-    )))
+            .collect(),
+    ))
 }
 
+/// A `FOREIGN KEY (column) REFERENCES other_table (other_column)` constraint.
+///
+/// `references` only exists for compatibility and must be `ForeignKeyAttribute::REFERENCES`.
+/// Panics otherwise; see `try_foreign_key` for the fallible form.
 pub fn foreign_key<T: Into<TableName>, A: Into<Vec<ForeignKeyAttribute>>>(
     column_name: Arc<Column>,
     references: ForeignKeyAttribute,
@@ -51,36 +204,192 @@ pub fn foreign_key<T: Into<TableName>, A: Into<Vec<ForeignKeyAttribute>>>(
     other_table_column: Arc<Column>,
     attributes: A,
 ) -> Arc<Column> {
-    Arc::new(Column::Constraint(format!(
-        "FOREIGN KEY ({column_name}) {references} {other_table_name} ({other_table_column}){attributes}",
-        column_name = column_name.name(),
-        references = references,
-        other_table_name = other_table_name.into().0,
-        other_table_column = other_table_column.name(),
-        attributes = {
-            let attributes = attributes.into();
-            if attributes.is_empty() {
-                "".to_string()
-            } else {
-                format!(" {}", attributes.iter().map(ToString::to_string).collect::<Vec<_>>().join(" "))
+    try_foreign_key(
+        column_name,
+        references,
+        other_table_name,
+        other_table_column,
+        attributes,
+    )
+    .unwrap_or_else(|data| panic!("{}", data))
+}
+
+/// Same as `foreign_key` but returns `SchemaError::InvalidForeignKey` instead of panicking.
+pub fn try_foreign_key<T: Into<TableName>, A: Into<Vec<ForeignKeyAttribute>>>(
+    column_name: Arc<Column>,
+    references: ForeignKeyAttribute,
+    other_table_name: T,
+    other_table_column: Arc<Column>,
+    attributes: A,
+) -> Result<Arc<Column>, SchemaError> {
+    if !matches!(references, ForeignKeyAttribute::REFERENCES) {
+        return Err(SchemaError::InvalidForeignKey(references.to_string()));
+    }
+    let (actions, deferred) = foreign_key_clauses(attributes.into())?;
+    Ok(Arc::new(Column::ForeignKey {
+        local: vec![column_name.name().to_owned()],
+        table: other_table_name.into().name,
+        references: vec![other_table_column.name().to_owned()],
+        actions,
+        deferred,
+    }))
+}
+
+/// Splits the attributes following the parent columns into `(trigger, action)` pairs and
+/// whether `DEFERRABLE INITIALLY DEFERRED` is given. The deprecated `ON_DELETE`/`ON_UPDATE`
+/// must be directly followed by one of the deprecated actions.
+#[allow(deprecated)]
+fn foreign_key_clauses(
+    attributes: Vec<ForeignKeyAttribute>,
+) -> Result<(Vec<(Trigger, ReferentialAction)>, bool), SchemaError> {
+    use ForeignKeyAttribute::*;
+
+    let mut actions = vec![];
+    let mut deferred = false;
+    let mut iter = attributes.into_iter();
+    while let Some(data) = iter.next() {
+        let trigger = match data {
+            ACTION(trigger, action) => {
+                actions.push((trigger, action));
+                continue;
             }
-        }
-    )))
+            DEFERRABLE_INITIALLY_DEFERRED => {
+                deferred = true;
+                continue;
+            }
+            ON_DELETE => Trigger::OnDelete,
+            ON_UPDATE => Trigger::OnUpdate,
+            data => return Err(SchemaError::InvalidForeignKey(data.to_string())),
+        };
+        let action = match iter.next() {
+            Some(SET_NULL) => ReferentialAction::SetNull,
+            Some(SET_DEFAULT) => ReferentialAction::SetDefault,
+            Some(CASCADE) => ReferentialAction::Cascade,
+            Some(RESTRICT) => ReferentialAction::Restrict,
+            Some(NO_ACTION) => ReferentialAction::NoAction,
+            _ => return Err(SchemaError::InvalidForeignKey(trigger.to_string())),
+        };
+        actions.push((trigger, action));
+    }
+    Ok((actions, deferred))
 }
 
-pub fn unique<K: AsRef<[Arc<Column>]>>(keys: K) -> Arc<Column> {
-    Arc::new(Column::Constraint(format!(
+/// `foreign_key` with `ON DELETE`/`ON UPDATE` actions, e.g.
+/// `&[(Trigger::OnDelete, ReferentialAction::Cascade)]`.
+pub fn foreign_key_with_actions<T: Into<TableName>>(
+    column_name: Arc<Column>,
+    other_table_name: T,
+    other_table_column: Arc<Column>,
+    actions: &[(Trigger, ReferentialAction)],
+) -> Arc<Column> {
+    Arc::new(Column::ForeignKey {
+        local: vec![column_name.name().to_owned()],
+        table: other_table_name.into().name,
+        references: vec![other_table_column.name().to_owned()],
+        actions: actions.to_vec(),
+        deferred: false,
+    })
+}
+
+/// A table-level `CHECK (expr)` constraint.
+pub fn check<T: Into<String>>(expr: T) -> Arc<Column> {
+    Arc::new(Column::Constraint(format!("CHECK ({})", expr.into())))
+}
+
+/// `check` with `{0}`, `{1}`, ... in `expr_template` replaced by the quoted names of `columns`,
+/// e.g. `check_columns(&[&start, &end], "{0} <= {1}")` for `CHECK ("start" <= "end")`.
+///
+/// Returns `SchemaError::InvalidCheckTemplate` unless the template uses exactly one placeholder
+/// index per column.
+pub fn check_columns(
+    columns: &[&Arc<Column>],
+    expr_template: &str,
+) -> Result<Arc<Column>, SchemaError> {
+    let mut expr = String::new();
+    let mut used = BTreeSet::new();
+    let mut rest = expr_template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|data| start + data)
+            .ok_or_else(|| SchemaError::InvalidCheckTemplate(expr_template.to_owned()))?;
+        let column = rest[start + 1..end]
+            .parse::<usize>()
+            .ok()
+            .and_then(|data| columns.get(data).map(|column| (data, column)))
+            .ok_or_else(|| SchemaError::InvalidCheckTemplate(expr_template.to_owned()))?;
+        used.insert(column.0);
+        expr.push_str(&rest[..start]);
+        expr.push_str(&quote_identifier(column.1.name()));
+        rest = &rest[end + 1..];
+    }
+    expr.push_str(rest);
+
+    if used.len() != columns.len() {
+        return Err(SchemaError::InvalidCheckTemplate(expr_template.to_owned()));
+    }
+    Ok(check(expr))
+}
+
+/// `CHECK ("column" LIKE 'pattern')`. `LIKE` is case-insensitive for ASCII, with `%` and `_` as
+/// wildcards.
+pub fn check_like<T: Into<String>>(column: &Arc<Column>, pattern: T) -> Arc<Column> {
+    check(format!(
+        "{} LIKE {}",
+        quote_identifier(column.name()),
+        escape_string(pattern)
+    ))
+}
+
+/// `CHECK ("column" GLOB 'pattern')`. `GLOB` is case-sensitive, with `*`, `?` and `[...]` as
+/// wildcards.
+pub fn check_glob<T: Into<String>>(column: &Arc<Column>, pattern: T) -> Arc<Column> {
+    check(format!(
+        "{} GLOB {}",
+        quote_identifier(column.name()),
+        escape_string(pattern)
+    ))
+}
+
+/// An inline `REFERENCES other_table (other_column)` followed by `attributes`, e.g.
+/// `DEFERRABLE_INITIALLY_DEFERRED` to check the reference at commit time.
+///
+/// Panics for attributes `try_foreign_key` rejects, e.g. a standalone `SET_NULL`.
+pub fn references<T: Into<TableName>, A: Into<Vec<ForeignKeyAttribute>>>(
+    other_table_name: T,
+    other_table_column: Arc<Column>,
+    attributes: A,
+) -> Attribute {
+    let mut clause = format!(
         "{} ({})",
-        Attribute::UNIQUE.name(),
+        other_table_name.into().name,
+        other_table_column.name()
+    );
+    let (actions, deferred) =
+        foreign_key_clauses(attributes.into()).unwrap_or_else(|data| panic!("{}", data));
+    for (trigger, action) in actions {
+        clause.push_str(&format!(" {} {}", trigger, action));
+    }
+    if deferred {
+        clause.push_str(" DEFERRABLE INITIALLY DEFERRED");
+    }
+    Attribute::INLINE_REFERENCES(clause)
+}
+
+pub fn unique<K: AsRef<[Arc<Column>]>>(keys: K) -> Arc<Column> {
+    Arc::new(Column::Unique(
         keys.as_ref()
             .iter()
-            .map(|data| data.name())
-            .collect::<Vec<_>>()
-            .join(", ")
-    )))
+            .map(|data| data.name().to_owned())
+            .collect(),
+    ))
 }
 
+/// `Ord` follows the declaration order, then the parameters of `VARCHAR`, `CHAR`, `DECIMAL`,
+/// `Raw` and `Declared`. It exists for canonical serialization and does not reflect any relation
+/// between the types themselves.
 #[allow(non_camel_case_types)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Type {
     INTEGER,
     INT,
@@ -103,11 +412,180 @@ pub enum Type {
     DATE,
     DATETIME,
     JSON,
+    /// `VARCHAR(n)`. SQLite does not enforce the length.
+    VARCHAR(u32),
+    /// `CHAR(n)`. SQLite does not enforce the length.
+    CHAR(u32),
+    /// `DECIMAL(precision, scale)`, stored with NUMERIC affinity. A scale of 0 renders as
+    /// `DECIMAL(precision)`. `Table::validate` rejects a scale greater than the precision.
+    DECIMAL(u8, u8),
+    /// `BINARY(n)` for fixed-size byte fields ported from other engines.
+    BINARY(u32),
+    /// `VARBINARY(n)` for variable-size byte fields ported from other engines.
+    VARBINARY(u32),
+    /// The untyped column of a STRICT table, which stores values as given. Outside STRICT
+    /// tables SQLite reads `ANY` as an unknown type name with NUMERIC affinity.
+    ANY,
+    /// A declared type rendered verbatim.
+    Raw(String),
+    /// A declared type rendered verbatim with the affinity intended by the schema author, which
+    /// `affinity` reports instead of deriving one from the name.
+    Declared(String, Affinity),
 }
 
 impl Type {
-    fn name(&self) -> &str {
+    /// Maps a declared type (e.g. from `PRAGMA table_info`) back to a `Type`, ignoring case and
+    /// extra whitespace, including the parameters of e.g. `VARCHAR(n)` and `DECIMAL(p, s)`.
+    /// Unknown declarations are kept as `Type::Raw`.
+    pub fn from_declared(declared: &str) -> Type {
+        let normalized = declared
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_ascii_uppercase();
+        if let Some(data) = Type::from_parameterized(&normalized) {
+            return data;
+        }
+        match normalized.as_str() {
+            "INTEGER" => Type::INTEGER,
+            "INT" => Type::INT,
+            "TINYINT" => Type::TINYINT,
+            "SMALLINT" => Type::SMALLINT,
+            "MEDIUMINT" => Type::MEDIUMINT,
+            "BIGINT" => Type::BIGINT,
+            "UNSIGNED BIG INT" => Type::UNSIGNED_BIG_INT,
+            "INT2" => Type::INT2,
+            "INT8" => Type::INT8,
+            "TEXT" => Type::TEXT,
+            "CLOB" => Type::CLOB,
+            "BLOB" => Type::BLOB,
+            "REAL" => Type::REAL,
+            "DOUBLE" => Type::DOUBLE,
+            "DOUBLE PRECISION" => Type::DOUBLE_PRECISION,
+            "FLOAT" => Type::FLOAT,
+            "NUMERIC" => Type::NUMERIC,
+            "BOOLEAN" => Type::BOOLEAN,
+            "DATE" => Type::DATE,
+            "DATETIME" => Type::DATETIME,
+            "JSON" => Type::JSON,
+            "ANY" => Type::ANY,
+            _ => Type::Raw(declared.to_owned()),
+        }
+    }
+
+    /// Parses `NAME(a)` or `NAME(a, b)` of a normalized declared type.
+    fn from_parameterized(normalized: &str) -> Option<Type> {
+        let (name, parameters) = normalized.strip_suffix(')')?.split_once('(')?;
+        let parameters = parameters.split(',').map(str::trim).collect::<Vec<_>>();
+        let data = match (name.trim_end(), parameters.as_slice()) {
+            ("VARCHAR", [length]) => Type::VARCHAR(length.parse().ok()?),
+            ("CHAR", [length]) => Type::CHAR(length.parse().ok()?),
+            ("BINARY", [length]) => Type::BINARY(length.parse().ok()?),
+            ("VARBINARY", [length]) => Type::VARBINARY(length.parse().ok()?),
+            ("DECIMAL", [precision]) => Type::DECIMAL(precision.parse().ok()?, 0),
+            ("DECIMAL", [precision, scale]) => {
+                Type::DECIMAL(precision.parse().ok()?, scale.parse().ok()?)
+            }
+            _ => return None,
+        };
+        Some(data)
+    }
+
+    /// The column affinity SQLite derives from the declared type. `Type::ANY` reports `BLOB`
+    /// since it never coerces values in a STRICT table, and `BINARY` and `VARBINARY` report the
+    /// intended `BLOB` although SQLite assigns them NUMERIC affinity.
+    pub fn affinity(&self) -> Affinity {
+        match self {
+            Type::INTEGER
+            | Type::INT
+            | Type::TINYINT
+            | Type::SMALLINT
+            | Type::MEDIUMINT
+            | Type::BIGINT
+            | Type::UNSIGNED_BIG_INT
+            | Type::INT2
+            | Type::INT8 => Affinity::INTEGER,
+            Type::TEXT | Type::CLOB | Type::VARCHAR(_) | Type::CHAR(_) => Affinity::TEXT,
+            Type::BLOB | Type::ANY | Type::BINARY(_) | Type::VARBINARY(_) => Affinity::BLOB,
+            Type::REAL | Type::DOUBLE | Type::DOUBLE_PRECISION | Type::FLOAT => Affinity::REAL,
+            Type::NUMERIC
+            | Type::BOOLEAN
+            | Type::DATE
+            | Type::DATETIME
+            | Type::JSON
+            | Type::DECIMAL(_, _) => Affinity::NUMERIC,
+            Type::Raw(value) => Affinity::from_declared(value),
+            Type::Declared(_, affinity) => *affinity,
+        }
+    }
+
+    /// A Rust expression reading `field` from a `rusqlite::Row` named `row`, for code generation.
+    pub fn rust_from_sql_expr(&self, field: &str) -> String {
+        let rust_type = match self {
+            Type::BOOLEAN => return format!("row.get::<_, i64>({:?})? != 0", field),
+            Type::DATE | Type::DATETIME | Type::JSON => "String",
+            Type::ANY => "rusqlite::types::Value",
+            _ => match self.affinity() {
+                Affinity::INTEGER => "i64",
+                Affinity::TEXT => "String",
+                Affinity::BLOB => "Vec<u8>",
+                Affinity::REAL | Affinity::NUMERIC => "f64",
+            },
+        };
+        format!("row.get::<_, {}>({:?})?", rust_type, field)
+    }
+
+    /// A Rust expression binding `self.field` as a parameter of `rusqlite::params!`, for code
+    /// generation. The counterpart of `rust_from_sql_expr`.
+    pub fn rust_to_sql_expr(&self, field: &str) -> String {
+        match self {
+            Type::BOOLEAN => format!("if self.{} {{ 1 }} else {{ 0 }}", field),
+            _ => format!("self.{}", field),
+        }
+    }
+
+    /// The `n` of `VARCHAR(n)`, `CHAR(n)`, `BINARY(n)` and `VARBINARY(n)`.
+    pub fn length(&self) -> Option<u32> {
+        match self {
+            Type::VARCHAR(length)
+            | Type::CHAR(length)
+            | Type::BINARY(length)
+            | Type::VARBINARY(length) => Some(*length),
+            _ => None,
+        }
+    }
+
+    /// `DECIMAL(precision)`, i.e. a scale of 0.
+    pub const fn decimal(precision: u8) -> Type {
+        Type::DECIMAL(precision, 0)
+    }
+
+    /// The `(precision, scale)` of `DECIMAL(precision, scale)`.
+    pub fn precision_scale(&self) -> Option<(u8, u8)> {
         match self {
+            Type::DECIMAL(precision, scale) => Some((*precision, *scale)),
+            _ => None,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.affinity() == Affinity::INTEGER
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.affinity() == Affinity::TEXT
+    }
+
+    pub fn is_real(&self) -> bool {
+        self.affinity() == Affinity::REAL
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.affinity() == Affinity::NUMERIC
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        let name = match self {
             Type::INTEGER => "INTEGER",
             Type::INT => "INT",
             Type::TINYINT => "TINYINT",
@@ -129,11 +607,57 @@ impl Type {
             Type::DATE => "DATE",
             Type::DATETIME => "DATETIME",
             Type::JSON => "JSON",
+            Type::ANY => "ANY",
+            Type::VARCHAR(length) => return format!("VARCHAR({})", length).into(),
+            Type::CHAR(length) => return format!("CHAR({})", length).into(),
+            Type::DECIMAL(precision, 0) => return format!("DECIMAL({})", precision).into(),
+            Type::DECIMAL(precision, scale) => {
+                return format!("DECIMAL({}, {})", precision, scale).into()
+            }
+            Type::BINARY(length) => return format!("BINARY({})", length).into(),
+            Type::VARBINARY(length) => return format!("VARBINARY({})", length).into(),
+            Type::Raw(value) => value,
+            Type::Declared(value, _) => value,
+        };
+        name.into()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Affinity {
+    INTEGER,
+    TEXT,
+    BLOB,
+    REAL,
+    NUMERIC,
+}
+
+impl Affinity {
+    /// The affinity rules of https://www.sqlite.org/datatype3.html#determination_of_column_affinity
+    fn from_declared(declared: &str) -> Affinity {
+        let declared = declared.to_ascii_uppercase();
+        if declared.contains("INT") {
+            Affinity::INTEGER
+        } else if ["CHAR", "CLOB", "TEXT"]
+            .iter()
+            .any(|data| declared.contains(data))
+        {
+            Affinity::TEXT
+        } else if declared.contains("BLOB") || declared.trim().is_empty() {
+            Affinity::BLOB
+        } else if ["REAL", "FLOA", "DOUB"]
+            .iter()
+            .any(|data| declared.contains(data))
+        {
+            Affinity::REAL
+        } else {
+            Affinity::NUMERIC
         }
     }
 }
 
 #[allow(non_camel_case_types)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Attribute {
     PRIMARY_KEY,
     ASC,
@@ -141,7 +665,13 @@ pub enum Attribute {
     UNIQUE,
     NOT_NULL,
     AUTOINCREMENT,
-    DEFAULT(String),
+    DEFAULT(DefaultValue),
+    CHECK(String),
+    COLLATE(CollationName),
+    /// An inline `REFERENCES` clause, built by `references`.
+    INLINE_REFERENCES(String),
+    /// `GENERATED ALWAYS AS (expr) VIRTUAL` or `STORED`.
+    GENERATED(String, GeneratedStorage),
 }
 
 impl Attribute {
@@ -153,26 +683,251 @@ impl Attribute {
             Attribute::UNIQUE => "UNIQUE".to_owned(),
             Attribute::NOT_NULL => "NOT NULL".to_owned(),
             Attribute::AUTOINCREMENT => "AUTOINCREMENT".to_owned(),
-            Attribute::DEFAULT(value) => format!("DEFAULT {}", escape_string(value)),
+            Attribute::DEFAULT(value) => format!("DEFAULT {}", value.literal()),
+            Attribute::CHECK(expr) => format!("CHECK ({})", expr),
+            Attribute::COLLATE(collation) => format!("COLLATE {}", collation.0),
+            Attribute::INLINE_REFERENCES(clause) => format!("REFERENCES {}", clause),
+            Attribute::GENERATED(expr, storage) => {
+                format!("GENERATED ALWAYS AS ({}) {}", expr, storage.name())
+            }
+        }
+    }
+
+    fn name_for(&self, dialect: &dyn Dialect) -> String {
+        let style = dialect.case_style();
+        match self {
+            Attribute::AUTOINCREMENT => style.apply(dialect.autoincrement()),
+            Attribute::DEFAULT(value) => {
+                format!("{} {}", style.apply("DEFAULT"), value.literal_for(dialect))
+            }
+            Attribute::CHECK(expr) => format!("{} ({})", style.apply("CHECK"), expr),
+            Attribute::COLLATE(collation) => {
+                format!("{} {}", style.apply("COLLATE"), collation.0)
+            }
+            Attribute::INLINE_REFERENCES(clause) => {
+                format!("{} {}", style.apply("REFERENCES"), clause)
+            }
+            Attribute::GENERATED(expr, storage) => format!(
+                "{} ({}) {}",
+                style.apply("GENERATED ALWAYS AS"),
+                expr,
+                style.apply(storage.name())
+            ),
+            _ => style.apply(&self.name()),
+        }
+    }
+}
+
+impl core::str::FromStr for Attribute {
+    type Err = ParseAttributeError;
+
+    /// Parses the form rendered into `CREATE TABLE`, e.g. `NOT NULL` or `DEFAULT 'value'`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseAttributeError(s.to_owned());
+        let s = s.trim();
+        let (keyword, rest) = match s.find(char::is_whitespace) {
+            Some(index) => (&s[..index], s[index..].trim()),
+            None => (s, ""),
+        };
+
+        match keyword.to_ascii_uppercase().as_str() {
+            "DEFAULT" if !rest.is_empty() => Ok(Attribute::DEFAULT(DefaultValue::parse(rest))),
+            "CHECK" if rest.starts_with('(') && rest.ends_with(')') => {
+                Ok(Attribute::CHECK(rest[1..rest.len() - 1].to_owned()))
+            }
+            "COLLATE" => rest.parse().map(Attribute::COLLATE).map_err(|_| err()),
+            "REFERENCES" if !rest.is_empty() => Ok(Attribute::INLINE_REFERENCES(rest.to_owned())),
+            _ => {
+                let normalized = s
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .to_ascii_uppercase();
+                match normalized.as_str() {
+                    "PRIMARY KEY" => Ok(Attribute::PRIMARY_KEY),
+                    "ASC" => Ok(Attribute::ASC),
+                    "DESC" => Ok(Attribute::DESC),
+                    "UNIQUE" => Ok(Attribute::UNIQUE),
+                    "NOT NULL" => Ok(Attribute::NOT_NULL),
+                    "AUTOINCREMENT" => Ok(Attribute::AUTOINCREMENT),
+                    _ => Err(err()),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseAttributeError(String);
+
+impl core::fmt::Display for ParseAttributeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "unknown attribute: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseAttributeError {}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratedStorage {
+    VIRTUAL,
+    STORED,
+}
+
+impl GeneratedStorage {
+    fn name(&self) -> &str {
+        match self {
+            GeneratedStorage::VIRTUAL => "VIRTUAL",
+            GeneratedStorage::STORED => "STORED",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DefaultValue {
+    /// A string literal. Always rendered single-quoted since SQLite may read a double-quoted
+    /// string as an identifier. Newlines are kept as is inside the literal, which SQLite
+    /// accepts; see `ValidationOptions::reject_nul_defaults` for NUL bytes.
+    Text(String),
+    /// Rendered verbatim, e.g. `CURRENT_TIMESTAMP` or `(1 + 1)`.
+    RawLiteral(String),
+    /// Rendered unquoted, e.g. `DEFAULT -1`.
+    Integer(i64),
+    /// Rendered unquoted with `{}` formatting, so `1500.0` becomes `DEFAULT 1500`. Infinities
+    /// become `9e999` and `-9e999`, which SQLite reads back as REAL infinities; NaN has no
+    /// literal and is reported as `SchemaError::InvalidDefaultValue`.
+    Real(f64),
+    /// `DEFAULT NULL`, the same as no default but stated explicitly.
+    Null,
+    /// Rendered as `1` or `0`, or as `TRUE` or `FALSE` for a `Dialect` with
+    /// `boolean_keywords`.
+    Bool(bool),
+}
+
+impl DefaultValue {
+    /// A single-quoted string literal becomes `Text`, `NULL` becomes `Null`, a number `Integer`
+    /// or `Real`, `TRUE`/`FALSE` `Bool` and anything else `RawLiteral`.
+    fn parse(value: &str) -> DefaultValue {
+        if value.eq_ignore_ascii_case("NULL") {
+            return DefaultValue::Null;
+        }
+        if let Ok(value) = value.parse::<i64>() {
+            return DefaultValue::Integer(value);
+        }
+        // `f64::from_str` also accepts `inf` and `NaN`, which SQLite reads as identifiers.
+        if value
+            .chars()
+            .all(|data| data.is_ascii_digit() || matches!(data, '+' | '-' | '.' | 'e' | 'E'))
+        {
+            if let Ok(value) = value.parse::<f64>() {
+                return DefaultValue::Real(value);
+            }
+        }
+        if value.eq_ignore_ascii_case("TRUE") {
+            return DefaultValue::Bool(true);
+        }
+        if value.eq_ignore_ascii_case("FALSE") {
+            return DefaultValue::Bool(false);
         }
+        if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+            let inner = &value[1..value.len() - 1];
+            if !inner.replace("''", "").contains('\'') {
+                return DefaultValue::Text(inner.replace("''", "'"));
+            }
+        }
+        DefaultValue::RawLiteral(value.to_owned())
+    }
+
+    fn literal(&self) -> String {
+        match self {
+            DefaultValue::Text(value) => escape_string(value.as_str()),
+            DefaultValue::RawLiteral(value) => value.to_owned(),
+            DefaultValue::Integer(value) => value.to_string(),
+            DefaultValue::Real(value) if value.is_infinite() => if value.is_sign_positive() {
+                "9e999"
+            } else {
+                "-9e999"
+            }
+            .to_owned(),
+            DefaultValue::Real(value) => value.to_string(),
+            DefaultValue::Null => "NULL".to_owned(),
+            DefaultValue::Bool(value) => if *value { "1" } else { "0" }.to_owned(),
+        }
+    }
+
+    fn literal_for(&self, dialect: &dyn Dialect) -> String {
+        match self {
+            DefaultValue::Bool(value) if dialect.boolean_keywords() => dialect
+                .case_style()
+                .apply(if *value { "TRUE" } else { "FALSE" }),
+            _ => self.literal(),
+        }
+    }
+}
+
+impl From<String> for DefaultValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
     }
 }
 
+impl From<&str> for DefaultValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.into())
+    }
+}
+
+impl From<i64> for DefaultValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<bool> for DefaultValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for DefaultValue {
+    fn from(value: f64) -> Self {
+        Self::Real(value)
+    }
+}
+
+/// The clauses of `foreign_key` and `references`.
+///
+/// The standalone triggers and actions are deprecated rather than removed so existing callers
+/// keep compiling. They are only accepted as an `ON_DELETE`/`ON_UPDATE` directly followed by an
+/// action; anything else, e.g. a lone `SET_NULL`, is rejected by `try_foreign_key` and makes
+/// `foreign_key` and `references` panic, so it can no longer be rendered.
 #[allow(non_camel_case_types)]
 pub enum ForeignKeyAttribute {
     REFERENCES,
+    #[deprecated(note = "use ForeignKeyAttribute::ACTION(Trigger::OnDelete, ..)")]
     ON_DELETE,
+    #[deprecated(note = "use ForeignKeyAttribute::ACTION(Trigger::OnUpdate, ..)")]
     ON_UPDATE,
+    #[deprecated(note = "use ForeignKeyAttribute::ACTION(.., ReferentialAction::SetNull)")]
     SET_NULL,
+    #[deprecated(note = "use ForeignKeyAttribute::ACTION(.., ReferentialAction::SetDefault)")]
     SET_DEFAULT,
+    #[deprecated(note = "use ForeignKeyAttribute::ACTION(.., ReferentialAction::Cascade)")]
     CASCADE,
+    #[deprecated(note = "use ForeignKeyAttribute::ACTION(.., ReferentialAction::Restrict)")]
     RESTRICT,
+    #[deprecated(note = "use ForeignKeyAttribute::ACTION(.., ReferentialAction::NoAction)")]
     NO_ACTION,
     DEFERRABLE_INITIALLY_DEFERRED,
+    /// `ON DELETE` or `ON UPDATE` followed by its action.
+    ACTION(Trigger, ReferentialAction),
 }
 
-impl std::fmt::Display for ForeignKeyAttribute {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ForeignKeyAttribute {
+    #[allow(deprecated)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         use ForeignKeyAttribute::*;
         match self {
             REFERENCES => write!(f, "REFERENCES"),
@@ -184,179 +939,6876 @@ impl std::fmt::Display for ForeignKeyAttribute {
             RESTRICT => write!(f, "RESTRICT"),
             NO_ACTION => write!(f, "NO ACTION"),
             DEFERRABLE_INITIALLY_DEFERRED => write!(f, "DEFERRABLE INITIALLY DEFERRED"),
+            ACTION(trigger, action) => write!(f, "{} {}", trigger, action),
         }
     }
 }
 
-fn escape_string<T: Into<String>>(value: T) -> String {
-    let value = value.into();
-    if value.contains('\'') {
-        format!("\'{}\'", value.replace('\'', "\'\'"))
-    } else {
-        format!("\'{}\'", value)
-    }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    OnDelete,
+    OnUpdate,
 }
 
-pub trait Table {
-    fn name(&self) -> &str;
+impl core::fmt::Display for Trigger {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Trigger::OnDelete => write!(f, "ON DELETE"),
+            Trigger::OnUpdate => write!(f, "ON UPDATE"),
+        }
+    }
+}
 
-    fn columns(&self) -> &[Arc<Column>];
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferentialAction {
+    Cascade,
+    SetNull,
+    SetDefault,
+    Restrict,
+    NoAction,
+}
 
-    fn indexes(&self) -> &[(String, Vec<Arc<Column>>)] {
-        &[]
+impl core::fmt::Display for ReferentialAction {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReferentialAction::Cascade => write!(f, "CASCADE"),
+            ReferentialAction::SetNull => write!(f, "SET NULL"),
+            ReferentialAction::SetDefault => write!(f, "SET DEFAULT"),
+            ReferentialAction::Restrict => write!(f, "RESTRICT"),
+            ReferentialAction::NoAction => write!(f, "NO ACTION"),
+        }
     }
+}
 
-    fn create_sql(&self) -> String {
-        format!(
-            "CREATE TABLE {} ({})",
-            self.name(),
-            self.columns()
-                .iter()
-                .map(|data| data.create_statement())
-                .collect::<Vec<_>>()
-                .join(", ")
-        )
-    }
+pub fn attach_database_sql(path: &str, schema_name: &str) -> String {
+    format!("ATTACH DATABASE {} AS {}", escape_string(path), schema_name)
+}
 
-    fn create_index(&self) -> Vec<String> {
-        let indexes = self.indexes();
-        if indexes.is_empty() {
-            return vec![];
-        } else {
-            indexes
-                .iter()
-                .map(|(name, columns)| {
-                    format!(
-                        "CREATE INDEX {} ON {} ({})",
-                        name,
-                        self.name(),
-                        columns
+pub fn detach_database_sql(schema_name: &str) -> String {
+    format!("DETACH DATABASE {}", schema_name)
+}
+
+/// Returns `n` comma-separated `?` placeholders, e.g. `?, ?, ?`.
+pub fn placeholders(n: usize) -> String {
+    vec!["?"; n].join(", ")
+}
+
+/// `ANALYZE`, or `ANALYZE "target"` for a single table or index.
+pub fn analyze_sql(target: Option<&str>) -> String {
+    match target {
+        Some(target) => format!("ANALYZE {}", quote_identifier(target)),
+        None => "ANALYZE".to_owned(),
+    }
+}
+
+/// Runs `analyze_sql(target)`.
+#[cfg(feature = "rusqlite")]
+pub fn analyze(conn: &rusqlite::Connection, target: Option<&str>) -> rusqlite::Result<()> {
+    conn.execute_batch(&analyze_sql(target))
+}
+
+/// `REINDEX`, or `REINDEX "target"` for a collation, table or index, e.g. after changing a
+/// collation's behavior.
+pub fn reindex_sql(target: Option<&str>) -> String {
+    match target {
+        Some(target) => format!("REINDEX {}", quote_identifier(target)),
+        None => "REINDEX".to_owned(),
+    }
+}
+
+/// Runs `reindex_sql(target)`.
+#[cfg(feature = "rusqlite")]
+pub fn reindex(conn: &rusqlite::Connection, target: Option<&str>) -> rusqlite::Result<()> {
+    conn.execute_batch(&reindex_sql(target))
+}
+
+pub fn integrity_check_sql() -> &'static str {
+    "PRAGMA integrity_check"
+}
+
+/// `PRAGMA foreign_key_check` for the whole database, or `PRAGMA foreign_key_check("table")`
+/// for `table` only.
+pub fn foreign_key_check_sql(table: Option<&str>) -> String {
+    match table {
+        Some(table) => format!("PRAGMA foreign_key_check({})", quote_identifier(table)),
+        None => "PRAGMA foreign_key_check".to_owned(),
+    }
+}
+
+/// The problems reported by `PRAGMA integrity_check`, empty if the database is intact.
+#[cfg(feature = "rusqlite")]
+pub fn integrity_check(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<String>> {
+    let mut statement = conn.prepare(integrity_check_sql())?;
+    let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+    let mut problems = vec![];
+    for data in rows {
+        let data = data?;
+        if data != "ok" {
+            problems.push(data);
+        }
+    }
+    Ok(problems)
+}
+
+/// A row of `PRAGMA foreign_key_check`.
+#[cfg(feature = "rusqlite")]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ForeignKeyViolation {
+    pub table: String,
+    /// `None` for a `WITHOUT ROWID` table.
+    pub rowid: Option<i64>,
+    pub parent: String,
+    /// The index of the foreign key in `PRAGMA foreign_key_list`.
+    pub fkid: i64,
+}
+
+#[cfg(feature = "rusqlite")]
+pub fn foreign_key_check(
+    conn: &rusqlite::Connection,
+    table: Option<&str>,
+) -> rusqlite::Result<Vec<ForeignKeyViolation>> {
+    let mut statement = conn.prepare(&foreign_key_check_sql(table))?;
+    let rows = statement.query_map([], |row| {
+        Ok(ForeignKeyViolation {
+            table: row.get(0)?,
+            rowid: row.get(1)?,
+            parent: row.get(2)?,
+            fkid: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// The version of the SQLite library behind `conn`, from `SELECT sqlite_version()`. A version
+/// string that does not parse is reported as `rusqlite::Error::FromSqlConversionFailure`.
+#[cfg(feature = "rusqlite")]
+pub fn sqlite_version(conn: &rusqlite::Connection) -> rusqlite::Result<SqliteVersion> {
+    let version = conn.query_row("SELECT sqlite_version()", [], |row| row.get::<_, String>(0))?;
+    version.parse().map_err(|data| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(data))
+    })
+}
+
+#[cfg(feature = "rusqlite")]
+fn supports(conn: &rusqlite::Connection, required: SqliteVersion) -> bool {
+    sqlite_version(conn).is_ok_and(|data| data >= required)
+}
+
+#[cfg(feature = "rusqlite")]
+pub fn supports_generated_columns(conn: &rusqlite::Connection) -> bool {
+    supports(conn, SqliteVersion::GENERATED_COLUMNS)
+}
+
+#[cfg(feature = "rusqlite")]
+pub fn supports_strict(conn: &rusqlite::Connection) -> bool {
+    supports(conn, SqliteVersion::STRICT)
+}
+
+#[cfg(feature = "rusqlite")]
+pub fn supports_returning(conn: &rusqlite::Connection) -> bool {
+    supports(conn, SqliteVersion::RETURNING)
+}
+
+#[cfg(feature = "rusqlite")]
+pub fn supports_drop_column(conn: &rusqlite::Connection) -> bool {
+    supports(conn, SqliteVersion::DROP_COLUMN)
+}
+
+/// One `ALTER TABLE ... RENAME COLUMN` per pair, in the given order.
+///
+/// Returns `SchemaError::DuplicateRename` if two pairs share a new name.
+pub fn rename_columns_sql<T: Table + ?Sized>(
+    table: &T,
+    renames: &[(&Arc<Column>, &str)],
+) -> Result<Vec<String>, SchemaError> {
+    let mut targets = BTreeSet::new();
+    for (_, to) in renames {
+        if !targets.insert(to.to_lowercase()) {
+            return Err(SchemaError::DuplicateRename((*to).to_owned()));
+        }
+    }
+
+    Ok(renames
+        .iter()
+        .map(|(from, to)| {
+            format!(
+                "ALTER TABLE {} RENAME COLUMN {} TO {}",
+                table.name(),
+                from.name(),
+                to
+            )
+        })
+        .collect())
+}
+
+/// `INSERT INTO target (columns) SELECT ...` with `select_body` appended verbatim after
+/// `SELECT`, e.g. to copy rows into a rebuilt table.
+pub fn insert_select_sql<T: Table + ?Sized>(
+    target_table: &T,
+    target_columns: &[Arc<Column>],
+    select_body: &str,
+) -> String {
+    format!(
+        "INSERT INTO {} ({}) SELECT {}",
+        target_table.name(),
+        target_columns
+            .iter()
+            .map(|data| data.name())
+            .collect::<Vec<_>>()
+            .join(", "),
+        select_body
+    )
+}
+
+/// The column changes from `current` to `desired`, returned by `diff_columns`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ColumnDiff {
+    /// Columns only in `desired`, in its order.
+    pub added: Vec<String>,
+    /// Columns only in `current`, in its order.
+    pub removed: Vec<String>,
+    /// `(name, current type, desired type)` of the columns whose type differs.
+    pub type_changed: Vec<(String, Type, Type)>,
+}
+
+/// Compares the columns of two tables by name, ignoring ASCII case as SQLite does. Constraints
+/// are not compared, and neither are the types of `Column::Raw` columns.
+pub fn diff_columns(current: &dyn Table, desired: &dyn Table) -> ColumnDiff {
+    let find = |table: &'_ dyn Table, name: &str| {
+        table
+            .data_columns()
+            .find(|data| data.name().eq_ignore_ascii_case(name))
+            .cloned()
+    };
+
+    let mut diff = ColumnDiff::default();
+    for data in desired.data_columns() {
+        match find(current, data.name()) {
+            None => diff.added.push(data.name().to_owned()),
+            Some(column) => {
+                if let (Some(from), Some(to)) = (column.data_type(), data.data_type()) {
+                    if from != to {
+                        diff.type_changed
+                            .push((data.name().to_owned(), from.clone(), to.clone()));
+                    }
+                }
+            }
+        }
+    }
+    for data in current.data_columns() {
+        if find(desired, data.name()).is_none() {
+            diff.removed.push(data.name().to_owned());
+        }
+    }
+    diff
+}
+
+/// The table rebuild from https://www.sqlite.org/lang_altertable.html for changes `ALTER TABLE`
+/// cannot make, e.g. a column type: `new` is created under a temporary name, the
+/// `(old column, new column)` pairs of `column_map` are copied over, `old` is dropped and the
+/// new table takes its name.
+///
+/// The caller is expected to run these in a transaction with `PRAGMA foreign_keys = OFF`, and
+/// to recreate indexes and triggers afterwards.
+pub fn rebuild_table_sql(
+    old: &dyn Table,
+    new: &dyn Table,
+    column_map: &[(&str, &str)],
+) -> Vec<String> {
+    let temp_name = format!("{}_rebuild", old.name());
+    vec![
+        format!(
+            "CREATE TABLE {} ({}){}",
+            temp_name,
+            new.columns()
+                .iter()
+                .map(|data| data.create_statement())
+                .collect::<Vec<_>>()
+                .join(", "),
+            table_options_sql(new, CaseStyle::Upper)
+        ),
+        format!(
+            "INSERT INTO {} ({}) SELECT {} FROM {}",
+            temp_name,
+            column_map
+                .iter()
+                .map(|(_, data)| *data)
+                .collect::<Vec<_>>()
+                .join(", "),
+            column_map
+                .iter()
+                .map(|(data, _)| *data)
+                .collect::<Vec<_>>()
+                .join(", "),
+            old.name()
+        ),
+        format!("DROP TABLE {}", old.name()),
+        format!("ALTER TABLE {} RENAME TO {}", temp_name, old.name()),
+    ]
+}
+
+/// `DELETE FROM` every child and then `parent`, for clearing tables whose foreign keys have no
+/// `ON DELETE CASCADE`. Children are ordered by the references of their `foreign_key`
+/// constraints and inline `references`, so a child referencing another child is cleared first.
+/// Children in a reference cycle keep the given order.
+pub fn delete_children_first_sql(parent: &dyn Table, children: &[&dyn Table]) -> Vec<String> {
+    let mut remaining = children.to_vec();
+    let mut sql = vec![];
+    while !remaining.is_empty() {
+        let index = remaining
+            .iter()
+            .position(|candidate| {
+                !remaining.iter().any(|data| {
+                    data.name() != candidate.name()
+                        && data
+                            .columns()
                             .iter()
-                            .map(|data| data.name())
-                            .collect::<Vec<_>>()
-                            .join(",")
-                    )
+                            .any(|data| data.referenced_table() == Some(candidate.name()))
                 })
-                .collect()
+            })
+            .unwrap_or(0);
+        sql.push(format!("DELETE FROM {}", remaining.remove(index).name()));
+    }
+    sql.push(format!("DELETE FROM {}", parent.name()));
+    sql
+}
+
+/// Converts a `unique` constraint into a `CREATE UNIQUE INDEX` on the same columns. SQLite
+/// enforces both the same way, the index only differs in having a name of its own in
+/// `sqlite_master`.
+///
+/// Returns `None` if `constraint` is not a `UNIQUE (...)` constraint.
+pub fn unique_index_sql<T: Table + ?Sized>(
+    table: &T,
+    index_name: &str,
+    constraint: &Column,
+) -> Option<String> {
+    match constraint.uncommented() {
+        Column::Unique(_) => Some(format!(
+            "CREATE UNIQUE INDEX {} ON {} ({})",
+            index_name,
+            table.name(),
+            constraint.constraint_columns().join(", ")
+        )),
+        Column::Constraint(value) if value.starts_with("UNIQUE (") => Some(format!(
+            "CREATE UNIQUE INDEX {} ON {} ({})",
+            index_name,
+            table.name(),
+            constraint.constraint_columns().join(", ")
+        )),
+        _ => None,
+    }
+}
+
+/// `CREATE UNIQUE INDEX` over expressions, e.g. `lower(trim(email))`, since SQLite only
+/// supports uniqueness of an expression through an index.
+pub fn unique_expr_index<T: Table + ?Sized>(index_name: &str, table: &T, exprs: &[&str]) -> String {
+    format!(
+        "CREATE UNIQUE INDEX {} ON {} ({})",
+        index_name,
+        table.name(),
+        exprs.join(", ")
+    )
+}
+
+/// https://www.sqlite.org/lang_keywords.html
+const KEYWORDS: &[&str] = &[
+    "ABORT",
+    "ACTION",
+    "ADD",
+    "AFTER",
+    "ALL",
+    "ALTER",
+    "ALWAYS",
+    "ANALYZE",
+    "AND",
+    "AS",
+    "ASC",
+    "ATTACH",
+    "AUTOINCREMENT",
+    "BEFORE",
+    "BEGIN",
+    "BETWEEN",
+    "BY",
+    "CASCADE",
+    "CASE",
+    "CAST",
+    "CHECK",
+    "COLLATE",
+    "COLUMN",
+    "COMMIT",
+    "CONFLICT",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "CURRENT",
+    "CURRENT_DATE",
+    "CURRENT_TIME",
+    "CURRENT_TIMESTAMP",
+    "DATABASE",
+    "DEFAULT",
+    "DEFERRABLE",
+    "DEFERRED",
+    "DELETE",
+    "DESC",
+    "DETACH",
+    "DISTINCT",
+    "DO",
+    "DROP",
+    "EACH",
+    "ELSE",
+    "END",
+    "ESCAPE",
+    "EXCEPT",
+    "EXCLUDE",
+    "EXCLUSIVE",
+    "EXISTS",
+    "EXPLAIN",
+    "FAIL",
+    "FILTER",
+    "FIRST",
+    "FOLLOWING",
+    "FOR",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GENERATED",
+    "GLOB",
+    "GROUP",
+    "GROUPS",
+    "HAVING",
+    "IF",
+    "IGNORE",
+    "IMMEDIATE",
+    "IN",
+    "INDEX",
+    "INDEXED",
+    "INITIALLY",
+    "INNER",
+    "INSERT",
+    "INSTEAD",
+    "INTERSECT",
+    "INTO",
+    "IS",
+    "ISNULL",
+    "JOIN",
+    "KEY",
+    "LAST",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "MATCH",
+    "MATERIALIZED",
+    "NATURAL",
+    "NO",
+    "NOT",
+    "NOTHING",
+    "NOTNULL",
+    "NULL",
+    "NULLS",
+    "OF",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OTHERS",
+    "OUTER",
+    "OVER",
+    "PARTITION",
+    "PLAN",
+    "PRAGMA",
+    "PRECEDING",
+    "PRIMARY",
+    "QUERY",
+    "RAISE",
+    "RANGE",
+    "RECURSIVE",
+    "REFERENCES",
+    "REGEXP",
+    "REINDEX",
+    "RELEASE",
+    "RENAME",
+    "REPLACE",
+    "RESTRICT",
+    "RETURNING",
+    "RIGHT",
+    "ROLLBACK",
+    "ROW",
+    "ROWS",
+    "SAVEPOINT",
+    "SELECT",
+    "SET",
+    "TABLE",
+    "TEMP",
+    "TEMPORARY",
+    "THEN",
+    "TIES",
+    "TO",
+    "TRANSACTION",
+    "TRIGGER",
+    "UNBOUNDED",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USING",
+    "VACUUM",
+    "VALUES",
+    "VIEW",
+    "VIRTUAL",
+    "WHEN",
+    "WHERE",
+    "WINDOW",
+    "WITH",
+    "WITHOUT",
+];
+
+fn is_keyword(value: &str) -> bool {
+    KEYWORDS.iter().any(|data| data.eq_ignore_ascii_case(value))
+}
+
+/// The comment heading generated schema, shared by `Table::create_sql_with_header` and
+/// `export_schema`. It names no version so checked-in files stay the same across releases.
+const GENERATED_HEADER: &str = "-- Generated by tinytable; do not edit.";
+
+/// `IF NOT EXISTS ` with a trailing space if `Table::if_not_exists`, or an empty string.
+fn if_not_exists_sql<T: Table + ?Sized>(table: &T) -> &'static str {
+    if table.if_not_exists() {
+        "IF NOT EXISTS "
+    } else {
+        ""
+    }
+}
+
+/// `CREATE [UNIQUE] INDEX` over `terms`, shared by `Table::create_index` and `IndexDef`.
+fn create_index_sql<I: IntoIterator<Item = String>>(
+    unique: bool,
+    if_not_exists: &str,
+    name: &str,
+    table: &str,
+    terms: I,
+) -> String {
+    format!(
+        "CREATE {}INDEX {}{} ON {} ({})",
+        if unique { "UNIQUE " } else { "" },
+        if_not_exists,
+        name,
+        table,
+        terms.into_iter().collect::<Vec<_>>().join(",")
+    )
+}
+
+/// The comma-separated table options following the closing paren of `CREATE TABLE`, with a
+/// leading space, or an empty string when there are none.
+fn table_options_sql<T: Table + ?Sized>(table: &T, style: CaseStyle) -> String {
+    let mut options = vec![];
+    if table.strict() {
+        options.push(style.apply("STRICT"));
+    }
+    if table.without_rowid() {
+        options.push(style.apply("WITHOUT ROWID"));
+    }
+    options.extend(table.table_options());
+    if options.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", options.join(", "))
+    }
+}
+
+/// Collapses whitespace and uppercases keywords and type names outside of quoted strings and
+/// identifiers. A leading `CREATE TABLE IF NOT EXISTS` is reduced to `CREATE TABLE`, as
+/// `sqlite_master` records it without the clause.
+fn normalize_sql(sql: &str) -> String {
+    let mut normalized = String::new();
+    let mut word = String::new();
+    let mut pending_space = false;
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            word.push(c);
+            if chars
+                .peek()
+                .is_none_or(|data| !(data.is_ascii_alphanumeric() || *data == '_'))
+            {
+                if pending_space && !normalized.is_empty() && !normalized.ends_with('(') {
+                    normalized.push(' ');
+                }
+                pending_space = false;
+                if is_keyword(&word) || !matches!(Type::from_declared(&word), Type::Raw(_)) {
+                    normalized.push_str(&word.to_ascii_uppercase());
+                } else {
+                    normalized.push_str(&word);
+                }
+                word.clear();
+            }
+            continue;
+        }
+
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+
+        let separated = !matches!(c, ')' | ',');
+        if pending_space && separated && !normalized.is_empty() && !normalized.ends_with('(') {
+            normalized.push(' ');
+        }
+        pending_space = c == ',';
+        normalized.push(c);
+
+        let close = match c {
+            '\'' | '"' | '`' => c,
+            '[' => ']',
+            _ => continue,
+        };
+        for data in chars.by_ref() {
+            normalized.push(data);
+            if data == close {
+                break;
+            }
+        }
+    }
+    match normalized.strip_prefix("CREATE TABLE IF NOT EXISTS ") {
+        Some(data) => format!("CREATE TABLE {}", data),
+        None => normalized,
+    }
+}
+
+fn quote_identifier(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// Quotes `value` as a string literal. SQLite only escapes `'` by doubling it; a backslash is an
+/// ordinary character and must be kept as is.
+fn escape_string<T: Into<String>>(value: T) -> String {
+    let value = value.into();
+    if value.contains('\'') {
+        format!("\'{}\'", value.replace('\'', "\'\'"))
+    } else {
+        format!("\'{}\'", value)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SchemaError {
+    NoColumns,
+    DuplicateColumn(String),
+    InvalidAutoincrement(String),
+    MissingValue(String),
+    InvalidCollationName(String),
+    UnknownConstraintColumn(String),
+    AutoincrementWithCompositeKey(String),
+    DuplicateRename(String),
+    NoPrimaryKey,
+    InvalidStrictType(String),
+    DefaultReferencesColumn(String),
+    InvalidGeneratedColumn(String),
+    InvalidDefaultValue(String),
+    InvalidCheckTemplate(String),
+    AutoincrementWithoutPrimaryKey(String),
+    UnsupportedByVersion(String),
+    InvalidDecimalScale(String),
+    InvalidForeignKey(String),
+    NotNullDefaultNull(String),
+}
+
+impl core::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use SchemaError::*;
+        match self {
+            NoColumns => write!(f, "table has no columns"),
+            DuplicateColumn(name) => write!(f, "duplicate column name: {}", name),
+            InvalidAutoincrement(name) => write!(
+                f,
+                "AUTOINCREMENT is only allowed on an INTEGER PRIMARY KEY: {}",
+                name
+            ),
+            MissingValue(name) => write!(
+                f,
+                "NOT NULL column without DEFAULT must be provided: {}",
+                name
+            ),
+            InvalidCollationName(name) => write!(f, "invalid collation name: {}", name),
+            UnknownConstraintColumn(name) => {
+                write!(f, "constraint references an unknown column: {}", name)
+            }
+            AutoincrementWithCompositeKey(name) => write!(
+                f,
+                "AUTOINCREMENT cannot be used with a table-level PRIMARY KEY: {}",
+                name
+            ),
+            DuplicateRename(name) => write!(f, "columns renamed to the same name: {}", name),
+            NoPrimaryKey => write!(f, "table has no PRIMARY KEY"),
+            InvalidStrictType(name) => write!(f, "type not allowed in a STRICT table: {}", name),
+            DefaultReferencesColumn(name) => write!(
+                f,
+                "DEFAULT cannot reference other columns, use a generated column instead: {}",
+                name
+            ),
+            InvalidGeneratedColumn(name) => write!(
+                f,
+                "generated column cannot have DEFAULT, PRIMARY KEY or AUTOINCREMENT: {}",
+                name
+            ),
+            InvalidDefaultValue(name) => write!(f, "invalid DEFAULT value: {}", name),
+            NotNullDefaultNull(name) => {
+                write!(f, "NOT NULL column cannot have DEFAULT NULL: {}", name)
+            }
+            InvalidCheckTemplate(template) => write!(
+                f,
+                "CHECK template placeholders do not match the columns: {}",
+                template
+            ),
+            AutoincrementWithoutPrimaryKey(name) => write!(
+                f,
+                "AUTOINCREMENT requires PRIMARY KEY on the same column: {}",
+                name
+            ),
+            UnsupportedByVersion(feature) => {
+                write!(f, "not supported by the target SQLite version: {}", feature)
+            }
+            InvalidDecimalScale(name) => {
+                write!(f, "DECIMAL scale exceeds its precision: {}", name)
+            }
+            InvalidForeignKey(clause) => write!(f, "invalid foreign key clause: {}", clause),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SchemaError {}
+
+/// Opt-in checks for `Table::validate_with`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ValidationOptions {
+    /// Report `SchemaError::NoPrimaryKey` for tables relying on the implicit rowid.
+    pub require_primary_key: bool,
+    /// Report `SchemaError::DefaultReferencesColumn` for a `DefaultValue::RawLiteral` that
+    /// mentions a column of the same table. SQLite rejects such defaults; a generated column is
+    /// usually what was meant. The check only matches words outside string literals.
+    pub check_default_references: bool,
+    /// Report `SchemaError::InvalidDefaultValue` for a `DefaultValue::Text` containing a NUL
+    /// byte, which SQLite truncates the statement at.
+    pub reject_nul_defaults: bool,
+}
+
+/// A SQLite release, for `Table::sql_for_version`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SqliteVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl SqliteVersion {
+    /// The first release supporting `WITHOUT ROWID` tables.
+    pub const WITHOUT_ROWID: Self = Self::new(3, 8, 2);
+    /// The first release supporting generated columns.
+    pub const GENERATED_COLUMNS: Self = Self::new(3, 31, 0);
+    /// The first release supporting `RETURNING`.
+    pub const RETURNING: Self = Self::new(3, 35, 0);
+    /// The first release supporting `ALTER TABLE ... DROP COLUMN`.
+    pub const DROP_COLUMN: Self = Self::new(3, 35, 0);
+    /// The first release supporting `STRICT` tables.
+    pub const STRICT: Self = Self::new(3, 37, 0);
+
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl core::fmt::Display for SqliteVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl core::str::FromStr for SqliteVersion {
+    type Err = ParseSqliteVersionError;
+
+    /// Parses the `major.minor.patch` form returned by `sqlite_version()`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseSqliteVersionError(s.to_owned());
+        let parts = s
+            .split('.')
+            .map(|data| data.parse::<u32>().map_err(|_| err()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match parts.as_slice() {
+            [major, minor, patch] => Ok(Self::new(*major, *minor, *patch)),
+            _ => Err(err()),
+        }
+    }
+}
+
+impl From<SqliteVersion> for (u32, u32, u32) {
+    fn from(value: SqliteVersion) -> Self {
+        (value.major, value.minor, value.patch)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseSqliteVersionError(String);
+
+impl core::fmt::Display for ParseSqliteVersionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid SQLite version: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseSqliteVersionError {}
+
+/// The pieces `Table::create_sql` is assembled from.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CreateParts {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub constraints: Vec<String>,
+}
+
+#[derive(Default)]
+pub enum ColumnOrder {
+    /// The order of `Table::columns`.
+    #[default]
+    Declared,
+    /// Columns by name, then constraints.
+    Alphabetical,
+    /// Inline `PRIMARY KEY` columns, then the rest, then constraints.
+    KeysFirst,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    ASC,
+    DESC,
+}
+
+impl SortOrder {
+    fn name(&self) -> &str {
+        match self {
+            SortOrder::ASC => "ASC",
+            SortOrder::DESC => "DESC",
+        }
+    }
+}
+
+/// The name of a collating sequence.
+///
+/// Custom collations must be registered on the connection before use, which cannot be checked
+/// here, so parsing only ensures the name is a plain identifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollationName(Cow<'static, str>);
+
+impl CollationName {
+    pub const BINARY: CollationName = CollationName(Cow::Borrowed("BINARY"));
+    pub const NOCASE: CollationName = CollationName(Cow::Borrowed("NOCASE"));
+    pub const RTRIM: CollationName = CollationName(Cow::Borrowed("RTRIM"));
+
+    pub fn is_builtin(&self) -> bool {
+        [Self::BINARY, Self::NOCASE, Self::RTRIM]
+            .iter()
+            .any(|data| data.0.eq_ignore_ascii_case(&self.0))
+    }
+}
+
+impl core::str::FromStr for CollationName {
+    type Err = SchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let valid = match chars.next() {
+            Some(first) => {
+                (first.is_ascii_alphabetic() || first == '_')
+                    && chars.all(|data| data.is_ascii_alphanumeric() || data == '_')
+            }
+            None => false,
+        };
+        if valid {
+            Ok(Self(Cow::Owned(s.to_owned())))
+        } else {
+            Err(SchemaError::InvalidCollationName(s.to_owned()))
+        }
+    }
+}
+
+/// A column of an index with an optional collation and sort order.
+pub struct IndexTerm {
+    column: Arc<Column>,
+    collation: Option<CollationName>,
+    order: Option<SortOrder>,
+}
+
+impl IndexTerm {
+    pub fn new(column: Arc<Column>) -> Self {
+        Self {
+            column,
+            collation: None,
+            order: None,
+        }
+    }
+
+    pub fn collate(mut self, collation: CollationName) -> Self {
+        self.collation = Some(collation);
+        self
+    }
+
+    pub fn order(mut self, order: SortOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    fn create_statement(&self) -> String {
+        let mut statement = self.column.name().to_owned();
+        if let Some(collation) = &self.collation {
+            statement.push_str(&format!(" COLLATE {}", collation.0));
+        }
+        if let Some(order) = &self.order {
+            statement.push_str(&format!(" {}", order.name()));
+        }
+        statement
+    }
+}
+
+impl From<Arc<Column>> for IndexTerm {
+    fn from(value: Arc<Column>) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Backend-specific spellings used by `Table::create_sql_for`.
+pub trait Dialect {
+    /// How `Attribute::AUTOINCREMENT` is rendered.
+    fn autoincrement(&self) -> &str {
+        "AUTOINCREMENT"
+    }
+
+    /// The case of keywords and type names, including those of `primary_key`, `unique` and
+    /// `foreign_key` constraints. Identifiers, literals, expressions and the text of raw and
+    /// hand-written constraints are rendered as given.
+    fn case_style(&self) -> CaseStyle {
+        CaseStyle::Upper
+    }
+
+    /// Renders `DefaultValue::Bool` as `TRUE`/`FALSE` instead of `1`/`0`. The keywords need
+    /// SQLite 3.23.0 or later.
+    fn boolean_keywords(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaseStyle {
+    #[default]
+    Upper,
+    Lower,
+}
+
+impl CaseStyle {
+    fn apply(&self, keyword: &str) -> String {
+        match self {
+            CaseStyle::Upper => keyword.to_owned(),
+            CaseStyle::Lower => keyword.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// The dialect used by `Table::create_sql`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sqlite;
+
+impl Dialect for Sqlite {}
+
+pub trait Table {
+    fn name(&self) -> &str;
+
+    fn columns(&self) -> &[Arc<Column>];
+
+    fn indexes(&self) -> &[(String, Vec<Arc<Column>>)] {
+        &[]
+    }
+
+    /// The `index`th entry of `columns`, constraints included.
+    fn column_at(&self, index: usize) -> Option<&Arc<Column>> {
+        self.columns().get(index)
+    }
+
+    /// The entries of `columns` that are not constraints, in order. Boxed to keep `Table`
+    /// usable as `dyn Table`.
+    fn data_columns(&self) -> Box<dyn Iterator<Item = &Arc<Column>> + '_> {
+        Box::new(self.columns().iter().filter(|data| !data.is_constraint()))
+    }
+
+    /// Appends `STRICT` to the `CREATE TABLE` statement, which limits column types to `INT`,
+    /// `INTEGER`, `REAL`, `TEXT`, `BLOB` and `ANY`. Requires SQLite 3.37.0 or later.
+    fn strict(&self) -> bool {
+        false
+    }
+
+    /// Appends `WITHOUT ROWID` to the `CREATE TABLE` statement. The table needs a
+    /// `PRIMARY KEY`.
+    fn without_rowid(&self) -> bool {
+        false
+    }
+
+    /// Extra table options appended after `STRICT` and `WITHOUT ROWID`, e.g. for options added
+    /// by newer SQLite versions.
+    fn table_options(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Renders `CREATE TABLE IF NOT EXISTS` and `CREATE INDEX IF NOT EXISTS` in `create_sql`,
+    /// `create_index` and the other statement builders.
+    fn if_not_exists(&self) -> bool {
+        false
+    }
+
+    fn create_sql(&self) -> String {
+        let mut sql = String::new();
+        self.write_create_sql(&mut sql)
+            .expect("a Display implementation returned an error unexpectedly");
+        sql
+    }
+
+    /// `create_sql` with attributes rendered in the idiom of `dialect`.
+    fn create_sql_for(&self, dialect: &dyn Dialect) -> String {
+        let style = dialect.case_style();
+        format!(
+            "{} {}{} ({}){}",
+            style.apply("CREATE TABLE"),
+            style.apply(if_not_exists_sql(self)),
+            self.name(),
+            self.columns()
+                .iter()
+                .map(|data| data.create_statement_for(dialect))
+                .collect::<Vec<_>>()
+                .join(", "),
+            table_options_sql(self, style)
+        )
+    }
+
+    /// `create_sql` with keywords and type names in `style`.
+    fn create_sql_styled(&self, style: CaseStyle) -> String {
+        struct Styled(CaseStyle);
+
+        impl Dialect for Styled {
+            fn case_style(&self) -> CaseStyle {
+                self.0
+            }
+        }
+
+        self.create_sql_for(&Styled(style))
+    }
+
+    /// `create_sql` with whitespace collapsed and keywords and type names uppercased,
+    /// approximating the `sql` column of `sqlite_master`. SQLite stores the statement as
+    /// written, so this only makes comparisons insensitive to formatting and case; it does not
+    /// resolve equivalent spellings such as `INT` and `INTEGER`, or differences in quoting.
+    fn normalized_create_sql(&self) -> String {
+        normalize_sql(&self.create_sql())
+    }
+
+    /// Whether the `sqlite_master` entry for this table matches `normalized_create_sql` once
+    /// normalized the same way. Returns `false` if the table does not exist.
+    #[cfg(feature = "rusqlite")]
+    fn matches_database(&self, conn: &rusqlite::Connection) -> bool {
+        conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [self.name()],
+            |row| row.get::<_, String>(0),
+        )
+        .map(|data| normalize_sql(&data) == self.normalized_create_sql())
+        .unwrap_or(false)
+    }
+
+    /// Runs `create_sql` against a throwaway in-memory database and returns the error SQLite
+    /// reports, if any.
+    #[cfg(feature = "rusqlite")]
+    fn check_sql(&self) -> rusqlite::Result<()> {
+        let conn = rusqlite::Connection::open_in_memory()?;
+        conn.execute(&self.create_sql(), [])?;
+        Ok(())
+    }
+
+    /// Writes `create_sql` to `w` without building the whole statement first.
+    fn write_create_sql(&self, w: &mut dyn core::fmt::Write) -> core::fmt::Result {
+        write!(
+            w,
+            "CREATE TABLE {}{} (",
+            if_not_exists_sql(self),
+            self.name()
+        )?;
+        for (index, data) in self.columns().iter().enumerate() {
+            if index != 0 {
+                w.write_str(", ")?;
+            }
+            w.write_str(&data.create_statement())?;
+        }
+        w.write_str(")")?;
+        w.write_str(&table_options_sql(self, CaseStyle::Upper))?;
+        Ok(())
+    }
+
+    /// Appends `create_sql` to `buf`, so a batch of statements can share one allocation. Any
+    /// separator between statements is left to the caller.
+    fn append_create_sql(&self, buf: &mut String) {
+        self.write_create_sql(buf)
+            .expect("a Display implementation returned an error unexpectedly");
+    }
+
+    #[cfg(feature = "std")]
+    fn write_create_sql_io(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
+        struct Adapter<'a> {
+            inner: &'a mut dyn std::io::Write,
+            error: Option<std::io::Error>,
+        }
+
+        impl core::fmt::Write for Adapter<'_> {
+            fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                self.inner.write_all(s.as_bytes()).map_err(|e| {
+                    self.error = Some(e);
+                    core::fmt::Error
+                })
+            }
+        }
+
+        let mut adapter = Adapter {
+            inner: w,
+            error: None,
+        };
+        match self.write_create_sql(&mut adapter) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adapter
+                .error
+                .unwrap_or_else(|| std::io::Error::other("formatter error"))),
+        }
+    }
+
+    /// `create_sql` with one column or constraint per line, followed by its comment if any.
+    fn create_sql_pretty(&self) -> String {
+        let columns = self.columns();
+        let mut sql = format!(
+            "CREATE TABLE {}{} (\n",
+            if_not_exists_sql(self),
+            self.name()
+        );
+        for (index, data) in columns.iter().enumerate() {
+            sql.push_str("    ");
+            sql.push_str(&data.create_statement());
+            if index + 1 != columns.len() {
+                sql.push(',');
+            }
+            if let Column::Commented(_, comment) = &**data {
+                sql.push_str(" -- ");
+                sql.push_str(&comment.replace('\n', " "));
+            }
+            sql.push('\n');
+        }
+        sql.push(')');
+        sql.push_str(&table_options_sql(self, CaseStyle::Upper));
+        sql
+    }
+
+    /// `create_sql` terminated with `;` for use in script files.
+    fn create_sql_terminated(&self) -> String {
+        format!("{};", self.create_sql())
+    }
+
+    fn create_parts(&self) -> CreateParts {
+        let (constraints, columns): (Vec<_>, Vec<_>) =
+            self.columns().iter().partition(|data| data.is_constraint());
+        CreateParts {
+            name: self.name().to_owned(),
+            columns: columns.iter().map(|data| data.create_statement()).collect(),
+            constraints: constraints
+                .iter()
+                .map(|data| data.create_statement())
+                .collect(),
+        }
+    }
+
+    /// Compares the DDL of both tables, respecting column order but ignoring constraint order.
+    fn create_sql_eq(&self, other: &dyn Table) -> bool {
+        self.create_sql_diff(other).is_none()
+    }
+
+    /// Describes the first difference `create_sql_eq` finds, if any.
+    fn create_sql_diff(&self, other: &dyn Table) -> Option<String> {
+        let lhs = self.create_parts();
+        let rhs = other.create_parts();
+
+        if lhs.name != rhs.name {
+            return Some(format!("table name: {} != {}", lhs.name, rhs.name));
+        }
+
+        for index in 0..lhs.columns.len().max(rhs.columns.len()) {
+            match (lhs.columns.get(index), rhs.columns.get(index)) {
+                (Some(a), Some(b)) if a == b => {}
+                (Some(a), Some(b)) => return Some(format!("column {}: {} != {}", index, a, b)),
+                (Some(a), None) => return Some(format!("column {}: {} is missing", index, a)),
+                (None, Some(b)) => return Some(format!("column {}: {} is extra", index, b)),
+                (None, None) => unreachable!(),
+            }
+        }
+
+        let mut lhs_constraints = lhs.constraints;
+        let mut rhs_constraints = rhs.constraints;
+        lhs_constraints.sort();
+        rhs_constraints.sort();
+        if let Some(data) = lhs_constraints
+            .iter()
+            .find(|data| !rhs_constraints.contains(data))
+        {
+            return Some(format!("constraint: {} is missing", data));
+        }
+        if let Some(data) = rhs_constraints
+            .iter()
+            .find(|data| !lhs_constraints.contains(data))
+        {
+            return Some(format!("constraint: {} is extra", data));
+        }
+        if lhs_constraints != rhs_constraints {
+            return Some("constraint: duplicated constraints differ".to_owned());
+        }
+
+        None
+    }
+
+    /// `create_sql` preceded by the `GENERATED_HEADER` line, for schema files checked into
+    /// version control.
+    fn create_sql_with_header(&self) -> String {
+        format!("{}\n{}", GENERATED_HEADER, self.create_sql())
+    }
+
+    /// `create_sql` with the inline `PRIMARY KEY` columns moved to the front. The other columns
+    /// keep their order and constraints stay last.
+    fn create_sql_keys_first(&self) -> String {
+        self.create_sql_sorted(ColumnOrder::KeysFirst)
+    }
+
+    fn create_sql_sorted(&self, order: ColumnOrder) -> String {
+        let mut columns = self.columns().iter().collect::<Vec<_>>();
+        match order {
+            ColumnOrder::Declared => {}
+            ColumnOrder::Alphabetical => {
+                columns.sort_by(|a, b| match (a.uncommented(), b.uncommented()) {
+                    (a, b) if !a.is_constraint() && !b.is_constraint() => a.name().cmp(b.name()),
+                    (a, b) => a.is_constraint().cmp(&b.is_constraint()),
+                })
+            }
+            ColumnOrder::KeysFirst => columns.sort_by_key(|data| match data.uncommented() {
+                Column::Column { .. }
+                    if data
+                        .attributes()
+                        .iter()
+                        .any(|data| matches!(data, Attribute::PRIMARY_KEY)) =>
+                {
+                    0
+                }
+                Column::Column { .. } | Column::Raw(_) => 1,
+                _ => 2,
+            }),
+        }
+        format!(
+            "CREATE TABLE {}{} ({}){}",
+            if_not_exists_sql(self),
+            self.name(),
+            columns
+                .iter()
+                .map(|data| data.create_statement())
+                .collect::<Vec<_>>()
+                .join(", "),
+            table_options_sql(self, CaseStyle::Upper)
+        )
+    }
+
+    fn index_terms(&self) -> &[(String, Vec<IndexTerm>)] {
+        &[]
+    }
+
+    /// SQLite only supports partial uniqueness through an index, so each `(name, columns, where)`
+    /// entry is emitted as `CREATE UNIQUE INDEX ... WHERE` by `create_index` instead of being
+    /// inlined in `create_sql`.
+    fn partial_uniques(&self) -> &[(String, Vec<Arc<Column>>, String)] {
+        &[]
+    }
+
+    fn create_index(&self) -> Vec<String> {
+        let indexes = self.indexes().iter().map(|(name, columns)| {
+            create_index_sql(
+                false,
+                if_not_exists_sql(self),
+                name,
+                self.name(),
+                columns.iter().map(|data| data.name().to_owned()),
+            )
+        });
+        let index_terms = self.index_terms().iter().map(|(name, terms)| {
+            create_index_sql(
+                false,
+                if_not_exists_sql(self),
+                name,
+                self.name(),
+                terms.iter().map(|data| data.create_statement()),
+            )
+        });
+        let partial_uniques = self
+            .partial_uniques()
+            .iter()
+            .map(|(name, columns, where_clause)| {
+                format!(
+                    "{} WHERE {}",
+                    create_index_sql(
+                        true,
+                        if_not_exists_sql(self),
+                        name,
+                        self.name(),
+                        columns.iter().map(|data| data.name().to_owned()),
+                    ),
+                    where_clause
+                )
+            });
+        indexes.chain(index_terms).chain(partial_uniques).collect()
+    }
+
+    /// `create_index` with each statement terminated with `;`.
+    fn create_index_terminated(&self) -> Vec<String> {
+        self.create_index()
+            .into_iter()
+            .map(|data| format!("{};", data))
+            .collect()
+    }
+
+    fn create_all_sql(&self) -> Vec<String> {
+        let mut sqls = vec![self.create_sql()];
+        sqls.extend(self.create_index());
+        sqls
+    }
+
+    fn insert_sql(&self, columns: &[Arc<Column>]) -> String {
+        format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            self.name(),
+            columns
+                .iter()
+                .map(|data| data.name())
+                .collect::<Vec<_>>()
+                .join(", "),
+            placeholders(columns.len())
+        )
+    }
+
+    /// Same as `insert_sql` but checks that every omitted column can take its default value,
+    /// i.e. it has a `DEFAULT`, is nullable, or is the `INTEGER PRIMARY KEY`.
+    fn try_insert_sql(&self, columns: &[Arc<Column>]) -> Result<String, SchemaError> {
+        for data in self.columns() {
+            if let Column::Column {
+                name, column_type, ..
+            } = data.uncommented()
+            {
+                if columns.iter().any(|provided| provided.name() == name) {
+                    continue;
+                }
+
+                let attributes = data.attributes();
+                let has = |f: fn(&Attribute) -> bool| attributes.iter().any(f);
+                let rowid = matches!(column_type, Type::INTEGER)
+                    && has(|data| matches!(data, Attribute::PRIMARY_KEY));
+                if has(|data| matches!(data, Attribute::NOT_NULL))
+                    && !has(|data| matches!(data, Attribute::DEFAULT(_)))
+                    && !rowid
+                {
+                    return Err(SchemaError::MissingValue(name.clone()));
+                }
+            }
+        }
+        Ok(self.insert_sql(columns))
+    }
+
+    /// `INSERT ... ON CONFLICT (conflict) DO UPDATE` that overwrites the non-conflicting columns.
+    /// Pass the same columns given to `unique` or `primary_key` as `conflict` to keep the target
+    /// in sync with the constraint.
+    fn upsert_sql(&self, columns: &[Arc<Column>], conflict: &[Arc<Column>]) -> String {
+        let updates = columns
+            .iter()
+            .filter(|data| !conflict.iter().any(|target| target.name() == data.name()))
+            .map(|data| format!("{name} = excluded.{name}", name = data.name()))
+            .collect::<Vec<_>>();
+        format!(
+            "{} ON CONFLICT ({}) {}",
+            self.insert_sql(columns),
+            conflict
+                .iter()
+                .map(|data| data.name())
+                .collect::<Vec<_>>()
+                .join(", "),
+            if updates.is_empty() {
+                "DO NOTHING".to_owned()
+            } else {
+                format!("DO UPDATE SET {}", updates.join(", "))
+            }
+        )
+    }
+
+    fn insert_named_sql(&self, columns: &[Arc<Column>]) -> String {
+        format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            self.name(),
+            columns
+                .iter()
+                .map(|data| data.name())
+                .collect::<Vec<_>>()
+                .join(", "),
+            columns
+                .iter()
+                .map(|data| format!(":{}", data.name()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    /// The table and column names that collide with an SQLite keyword and may need renaming.
+    fn keyword_collisions(&self) -> Vec<String> {
+        let table_name = Some(self.name()).filter(|data| is_keyword(data));
+        let column_names = self
+            .columns()
+            .iter()
+            .filter_map(|data| match data.uncommented() {
+                Column::Column { name, .. } if is_keyword(name) => Some(name.as_str()),
+                Column::Raw(_) if is_keyword(data.name()) => Some(data.name()),
+                _ => None,
+            });
+        table_name
+            .into_iter()
+            .chain(column_names)
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+
+    /// Runs all schema checks and returns every violation instead of stopping at the first.
+    fn validate(&self) -> Result<(), Vec<SchemaError>> {
+        self.validate_with(&ValidationOptions::default())
+    }
+
+    /// `validate` plus the opt-in checks enabled in `options`.
+    fn validate_with(&self, options: &ValidationOptions) -> Result<(), Vec<SchemaError>> {
+        let mut errors = vec![];
+
+        if !self.columns().iter().any(|data| !data.is_constraint()) {
+            errors.push(SchemaError::NoColumns);
+        }
+
+        let has_table_primary_key = self
+            .columns()
+            .iter()
+            .any(|data| data.is_table_primary_key());
+
+        let mut names = BTreeSet::new();
+        for data in self.columns() {
+            if let Column::Raw(_) = data.uncommented() {
+                if !names.insert(data.name().to_ascii_lowercase()) {
+                    errors.push(SchemaError::DuplicateColumn(data.name().to_owned()));
+                }
+            }
+
+            if let Column::Column {
+                name,
+                column_type,
+                attributes,
+            } = data.uncommented()
+            {
+                if !names.insert(name.to_ascii_lowercase()) {
+                    errors.push(SchemaError::DuplicateColumn(name.clone()));
+                }
+
+                let attributes = attributes.as_deref().unwrap_or_default();
+                let has = |f: fn(&Attribute) -> bool| attributes.iter().any(f);
+                if let Some(error) = data.autoincrement_error(has_table_primary_key) {
+                    errors.push(error);
+                }
+
+                if has(|data| matches!(data, Attribute::GENERATED(..)))
+                    && has(|data| {
+                        matches!(
+                            data,
+                            Attribute::DEFAULT(_)
+                                | Attribute::PRIMARY_KEY
+                                | Attribute::AUTOINCREMENT
+                        )
+                    })
+                {
+                    errors.push(SchemaError::InvalidGeneratedColumn(name.clone()));
+                }
+
+                if self.strict()
+                    && !matches!(
+                        Type::from_declared(&column_type.name()),
+                        Type::INT
+                            | Type::INTEGER
+                            | Type::REAL
+                            | Type::TEXT
+                            | Type::BLOB
+                            | Type::ANY
+                    )
+                {
+                    errors.push(SchemaError::InvalidStrictType(name.clone()));
+                }
+
+                if matches!(column_type.precision_scale(), Some((precision, scale)) if scale > precision)
+                {
+                    errors.push(SchemaError::InvalidDecimalScale(name.clone()));
+                }
+
+                if has(|data| matches!(data, Attribute::NOT_NULL))
+                    && has(|data| matches!(data, Attribute::DEFAULT(DefaultValue::Null)))
+                {
+                    errors.push(SchemaError::NotNullDefaultNull(name.clone()));
+                }
+
+                if has(
+                    |data| matches!(data, Attribute::DEFAULT(DefaultValue::Real(value)) if value.is_nan()),
+                ) {
+                    errors.push(SchemaError::InvalidDefaultValue(name.clone()));
+                }
+            }
+        }
+
+        for data in self.columns() {
+            for name in data.constraint_columns() {
+                if !names.contains(&name.to_ascii_lowercase()) {
+                    errors.push(SchemaError::UnknownConstraintColumn(name.to_owned()));
+                }
+            }
+        }
+
+        if options.require_primary_key && !self.has_primary_key() {
+            errors.push(SchemaError::NoPrimaryKey);
+        }
+
+        if options.reject_nul_defaults {
+            for data in self.columns() {
+                if data.attributes().iter().any(|data| {
+                    matches!(data, Attribute::DEFAULT(DefaultValue::Text(value)) if value.contains('\0'))
+                }) {
+                    errors.push(SchemaError::InvalidDefaultValue(data.name().to_owned()));
+                }
+            }
+        }
+
+        if options.check_default_references {
+            for data in self.columns() {
+                let expr = data.attributes().iter().find_map(|data| match data {
+                    Attribute::DEFAULT(DefaultValue::RawLiteral(expr)) => Some(expr),
+                    _ => None,
+                });
+                let expr = match expr {
+                    Some(expr) => expr,
+                    None => continue,
+                };
+                let mut in_string = false;
+                let referenced = expr
+                    .split(|c: char| {
+                        if c == '\'' {
+                            in_string = !in_string;
+                        }
+                        in_string || !(c.is_ascii_alphanumeric() || c == '_')
+                    })
+                    .any(|word| names.contains(&word.to_ascii_lowercase()));
+                if referenced {
+                    errors.push(SchemaError::DefaultReferencesColumn(data.name().to_owned()));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The tables named by `foreign_key` constraints and inline `references`, in declaration
+    /// order without duplicates.
+    ///
+    /// A `foreign_key` constraint is read from its stored parent table. A hand-written
+    /// `Column::Constraint` and an inline `references` are parsed instead, taking the text between
+    /// `REFERENCES ` and the following ` (`, so quoted names keep their quotes and
+    /// schema-qualified names are returned as `schema.table`.
+    fn referenced_tables(&self) -> Vec<String> {
+        let mut tables = Vec::<String>::new();
+        for data in self.columns() {
+            if let Some(table) = data.referenced_table() {
+                if !tables.iter().any(|data| data == table) {
+                    tables.push(table.to_owned());
+                }
+            }
+        }
+        tables
+    }
+
+    /// Whether a column has `PRIMARY KEY` or a `primary_key` constraint exists. Tables without
+    /// one fall back to the implicit rowid.
+    fn has_primary_key(&self) -> bool {
+        !self.primary_key_columns().is_empty()
+    }
+
+    /// The columns of the `primary_key` constraint, or else of the column with `PRIMARY KEY`.
+    fn primary_key_columns(&self) -> Vec<&str> {
+        if let Some(data) = self
+            .columns()
+            .iter()
+            .find(|data| data.is_table_primary_key())
+        {
+            return data.constraint_columns();
+        }
+
+        self.columns()
+            .iter()
+            .filter(|data| {
+                data.attributes()
+                    .iter()
+                    .any(|data| matches!(data, Attribute::PRIMARY_KEY))
+            })
+            .map(|data| data.name())
+            .collect()
+    }
+
+    /// Same as `create_sql` but returns the first error reported by `validate`.
+    fn try_create_sql(&self) -> Result<String, SchemaError> {
+        self.try_create_sql_with(&ValidationOptions::default())
+    }
+
+    /// Same as `create_sql` but returns the first error reported by `validate_with`.
+    fn try_create_sql_with(&self, options: &ValidationOptions) -> Result<String, SchemaError> {
+        self.validate_with(options)
+            .map_err(|mut data| data.remove(0))?;
+        Ok(self.create_sql())
+    }
+
+    /// `create_sql` for a database running SQLite `version`.
+    ///
+    /// Returns `SchemaError::UnsupportedByVersion` if the table uses `WITHOUT ROWID` (3.8.2),
+    /// generated columns (3.31.0) or `STRICT` (3.37.0) and `version` is older.
+    fn sql_for_version(&self, version: SqliteVersion) -> Result<String, SchemaError> {
+        let generated = self.columns().iter().any(|data| {
+            data.attributes()
+                .iter()
+                .any(|data| matches!(data, Attribute::GENERATED(..)))
+        });
+        let features = [
+            (
+                self.without_rowid(),
+                "WITHOUT ROWID",
+                SqliteVersion::WITHOUT_ROWID,
+            ),
+            (
+                generated,
+                "GENERATED ALWAYS",
+                SqliteVersion::GENERATED_COLUMNS,
+            ),
+            (self.strict(), "STRICT", SqliteVersion::STRICT),
+        ];
+        for (used, feature, required) in features {
+            if used && version < required {
+                return Err(SchemaError::UnsupportedByVersion(format!(
+                    "{} requires {}",
+                    feature, required
+                )));
+            }
+        }
+        Ok(self.create_sql())
+    }
+
+    /// SQLite adds one column per `ALTER TABLE` statement, so this returns one statement per
+    /// column.
+    ///
+    /// Panics if a column cannot be added by `ALTER TABLE` (a constraint, `PRIMARY KEY`,
+    /// `UNIQUE`, or `NOT NULL` without `DEFAULT`).
+    fn add_columns_sql(&self, columns: &[Arc<Column>]) -> Vec<String> {
+        columns
+            .iter()
+            .map(|data| {
+                data.check_add_column();
+                format!(
+                    "ALTER TABLE {} ADD COLUMN {}",
+                    self.name(),
+                    data.create_statement()
+                )
+            })
+            .collect()
+    }
+}
+
+impl<T: Table + ?Sized> Table for Box<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn columns(&self) -> &[Arc<Column>] {
+        (**self).columns()
+    }
+
+    fn indexes(&self) -> &[(String, Vec<Arc<Column>>)] {
+        (**self).indexes()
+    }
+
+    fn index_terms(&self) -> &[(String, Vec<IndexTerm>)] {
+        (**self).index_terms()
+    }
+
+    fn partial_uniques(&self) -> &[(String, Vec<Arc<Column>>, String)] {
+        (**self).partial_uniques()
+    }
+
+    fn strict(&self) -> bool {
+        (**self).strict()
+    }
+
+    fn without_rowid(&self) -> bool {
+        (**self).without_rowid()
+    }
+
+    fn table_options(&self) -> Vec<String> {
+        (**self).table_options()
+    }
+
+    fn if_not_exists(&self) -> bool {
+        (**self).if_not_exists()
+    }
+
+    fn create_sql(&self) -> String {
+        (**self).create_sql()
+    }
+
+    fn create_index(&self) -> Vec<String> {
+        (**self).create_index()
+    }
+}
+
+impl<T: Table + ?Sized> Table for Arc<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn columns(&self) -> &[Arc<Column>] {
+        (**self).columns()
+    }
+
+    fn indexes(&self) -> &[(String, Vec<Arc<Column>>)] {
+        (**self).indexes()
+    }
+
+    fn index_terms(&self) -> &[(String, Vec<IndexTerm>)] {
+        (**self).index_terms()
+    }
+
+    fn partial_uniques(&self) -> &[(String, Vec<Arc<Column>>, String)] {
+        (**self).partial_uniques()
+    }
+
+    fn strict(&self) -> bool {
+        (**self).strict()
+    }
+
+    fn without_rowid(&self) -> bool {
+        (**self).without_rowid()
+    }
+
+    fn table_options(&self) -> Vec<String> {
+        (**self).table_options()
+    }
+
+    fn if_not_exists(&self) -> bool {
+        (**self).if_not_exists()
+    }
+
+    fn create_sql(&self) -> String {
+        (**self).create_sql()
+    }
+
+    fn create_index(&self) -> Vec<String> {
+        (**self).create_index()
+    }
+}
+
+/// A `TableDef` that passed `Table::validate`, or the first error it reported.
+pub fn table<T: Into<String>>(name: T, columns: Vec<Arc<Column>>) -> Result<TableDef, SchemaError> {
+    let table = TableDef::new(name, columns);
+    table.validate().map_err(|mut data| data.remove(0))?;
+    Ok(table)
+}
+
+/// An owned table definition.
+///
+/// `cached_create_sql` memoizes the `CREATE TABLE` statement. This is only
+/// a good fit for immutable definitions: the cache is reset by `push_column`
+/// and `push_index`, but nothing else can observe a change to the schema.
+pub struct TableDef {
+    name: String,
+    columns: Vec<Arc<Column>>,
+    indexes: Vec<(String, Vec<Arc<Column>>)>,
+    if_not_exists: bool,
+    create_sql: core::cell::OnceCell<String>,
+}
+
+impl TableDef {
+    pub fn new<T: Into<String>, C: Into<Vec<Arc<Column>>>>(name: T, columns: C) -> Self {
+        Self {
+            name: name.into(),
+            columns: columns.into(),
+            indexes: Vec::new(),
+            if_not_exists: false,
+            create_sql: core::cell::OnceCell::new(),
+        }
+    }
+
+    /// Builds the columns in key order, so that the statement does not depend on how `map` was
+    /// assembled.
+    pub fn from_sorted_map<T: Into<String>>(
+        name: T,
+        map: BTreeMap<String, (Type, Vec<Attribute>)>,
+    ) -> Self {
+        Self::new(
+            name,
+            map.into_iter()
+                .map(|(name, (column_type, attributes))| column(name, column_type, attributes))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn push_column(&mut self, column: Arc<Column>) -> &mut Self {
+        self.columns.push(column);
+        self.create_sql.take();
+        self
+    }
+
+    pub fn push_index<T: Into<String>, K: Into<Vec<Arc<Column>>>>(
+        &mut self,
+        name: T,
+        keys: K,
+    ) -> &mut Self {
+        self.indexes.push((name.into(), keys.into()));
+        self.create_sql.take();
+        self
+    }
+
+    /// A table named after `self` with the columns, constraints and indexes of `extension`
+    /// appended to its own.
+    ///
+    /// Returns `SchemaError::DuplicateColumn` if `extension` has a column of the same name,
+    /// ignoring ASCII case.
+    pub fn merge(&self, extension: &TableDef) -> Result<TableDef, SchemaError> {
+        for data in extension
+            .columns
+            .iter()
+            .filter(|data| !data.is_constraint())
+        {
+            if self
+                .columns
+                .iter()
+                .filter(|data| !data.is_constraint())
+                .any(|column| column.name().eq_ignore_ascii_case(data.name()))
+            {
+                return Err(SchemaError::DuplicateColumn(data.name().to_owned()));
+            }
+        }
+
+        let mut table = TableDef::new(
+            self.name.to_owned(),
+            self.columns
+                .iter()
+                .chain(&extension.columns)
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+        table.indexes = self
+            .indexes
+            .iter()
+            .chain(&extension.indexes)
+            .cloned()
+            .collect();
+        table.if_not_exists = self.if_not_exists;
+        Ok(table)
+    }
+
+    /// Sets `Table::if_not_exists`, making the table and index statements idempotent.
+    pub fn set_if_not_exists(&mut self, if_not_exists: bool) -> &mut Self {
+        self.if_not_exists = if_not_exists;
+        self.create_sql.take();
+        self
+    }
+
+    /// Returns `create_sql`, generating it on the first call only.
+    pub fn cached_create_sql(&self) -> &str {
+        self.create_sql.get_or_init(|| self.create_sql())
+    }
+}
+
+impl<S: Into<String>> From<(S, Vec<Arc<Column>>)> for TableDef {
+    fn from((name, columns): (S, Vec<Arc<Column>>)) -> Self {
+        Self::new(name, columns)
+    }
+}
+
+/// A standalone `CREATE INDEX`, e.g. for `export_schema`.
+pub struct IndexDef {
+    name: String,
+    table: TableName,
+    columns: Vec<Arc<Column>>,
+    unique: bool,
+    if_not_exists: bool,
+}
+
+impl IndexDef {
+    pub fn new<T: Into<String>, U: Into<TableName>, K: Into<Vec<Arc<Column>>>>(
+        name: T,
+        table: U,
+        columns: K,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            table: table.into(),
+            columns: columns.into(),
+            unique: false,
+            if_not_exists: false,
+        }
+    }
+
+    /// Renders `CREATE UNIQUE INDEX`.
+    pub fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    /// Renders `CREATE INDEX IF NOT EXISTS`, like `Table::if_not_exists` does for table indexes.
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    pub fn create_sql(&self) -> String {
+        create_index_sql(
+            self.unique,
+            if self.if_not_exists {
+                "IF NOT EXISTS "
+            } else {
+                ""
+            },
+            &self.name,
+            &self.table.name,
+            self.columns.iter().map(|data| data.name().to_owned()),
+        )
+    }
+}
+
+/// A script creating `tables` followed by their own indexes and `indexes`, with foreign key
+/// enforcement turned off while it runs. Every statement is terminated with `;` and put on its
+/// own line, after the `GENERATED_HEADER` comment.
+pub fn export_schema(tables: &[&dyn Table], indexes: &[IndexDef]) -> String {
+    let mut lines = vec![
+        GENERATED_HEADER.to_owned(),
+        "PRAGMA foreign_keys=OFF;".to_owned(),
+    ];
+    lines.extend(tables.iter().map(|data| data.create_sql_terminated()));
+    lines.extend(
+        tables
+            .iter()
+            .flat_map(|data| data.create_index_terminated()),
+    );
+    lines.extend(indexes.iter().map(|data| format!("{};", data.create_sql())));
+    lines.push("PRAGMA foreign_keys=ON;".to_owned());
+    let mut script = lines.join("\n");
+    script.push('\n');
+    script
+}
+
+/// Builds a `TableDef` column by column, appending constraints as they are declared.
+pub struct TableBuilder {
+    table: TableDef,
+}
+
+impl TableBuilder {
+    pub fn new<T: Into<String>>(name: T) -> Self {
+        Self {
+            table: TableDef::new(name, vec![]),
+        }
+    }
+
+    pub fn column(mut self, column: Arc<Column>) -> Self {
+        self.table.push_column(column);
+        self
+    }
+
+    pub fn index<T: Into<String>, K: Into<Vec<Arc<Column>>>>(mut self, name: T, keys: K) -> Self {
+        self.table.push_index(name, keys);
+        self
+    }
+
+    /// Appends `primary_key(keys)`.
+    pub fn primary_key_of(self, keys: &[Arc<Column>]) -> Self {
+        self.column(primary_key(keys))
+    }
+
+    /// Appends `unique(keys)`.
+    pub fn unique_of(self, keys: &[Arc<Column>]) -> Self {
+        self.column(unique(keys))
+    }
+
+    /// Appends `check(expr)`.
+    pub fn check_expr<T: Into<String>>(self, expr: T) -> Self {
+        self.column(check(expr))
+    }
+
+    /// Renders `IF NOT EXISTS` for the table and its indexes.
+    pub fn if_not_exists(mut self) -> Self {
+        self.table.set_if_not_exists(true);
+        self
+    }
+
+    pub fn build(self) -> TableDef {
+        self.table
+    }
+}
+
+#[cfg(feature = "ir")]
+impl TableDef {
+    /// Converts the definition into plain data for other schema tools.
+    pub fn to_ir(&self) -> SchemaIr {
+        let mut columns = vec![];
+        let mut constraints = vec![];
+        for data in &self.columns {
+            match data.uncommented() {
+                Column::Column {
+                    name, column_type, ..
+                } => columns.push(ColumnIr {
+                    name: name.to_owned(),
+                    declared_type: column_type.name().into_owned(),
+                    affinity: column_type.affinity(),
+                    attributes: data.attributes().iter().map(|data| data.name()).collect(),
+                }),
+                Column::Constraint(value) => {
+                    let kind = if value.starts_with("PRIMARY KEY (") {
+                        ConstraintKind::PrimaryKey
+                    } else if value.starts_with("UNIQUE (") {
+                        ConstraintKind::Unique
+                    } else if value.starts_with("FOREIGN KEY (") {
+                        ConstraintKind::ForeignKey
+                    } else {
+                        ConstraintKind::Other
+                    };
+                    let references = value.split_once(") REFERENCES ").and_then(|(_, rest)| {
+                        let (table, rest) = rest.split_once(" (")?;
+                        let (columns, _) = rest.split_once(')')?;
+                        Some((
+                            table.to_owned(),
+                            columns
+                                .split(',')
+                                .map(|data| data.trim().to_owned())
+                                .collect(),
+                        ))
+                    });
+                    constraints.push(ConstraintIr {
+                        kind,
+                        columns: data
+                            .constraint_columns()
+                            .into_iter()
+                            .map(ToOwned::to_owned)
+                            .collect(),
+                        references,
+                        sql: value.to_owned(),
+                    });
+                }
+                Column::PrimaryKey(keys) => constraints.push(ConstraintIr {
+                    kind: ConstraintKind::PrimaryKey,
+                    columns: keys.to_owned(),
+                    references: None,
+                    sql: data.create_statement(),
+                }),
+                Column::Unique(keys) => constraints.push(ConstraintIr {
+                    kind: ConstraintKind::Unique,
+                    columns: keys.to_owned(),
+                    references: None,
+                    sql: data.create_statement(),
+                }),
+                Column::ForeignKey {
+                    local,
+                    table,
+                    references,
+                    ..
+                } => constraints.push(ConstraintIr {
+                    kind: ConstraintKind::ForeignKey,
+                    columns: local.to_owned(),
+                    references: Some((table.to_owned(), references.to_owned())),
+                    sql: data.create_statement(),
+                }),
+                Column::Raw(value) => {
+                    let declared_type = value.split_whitespace().nth(1).unwrap_or_default();
+                    columns.push(ColumnIr {
+                        name: data.name().to_owned(),
+                        declared_type: declared_type.to_owned(),
+                        affinity: Type::from_declared(declared_type).affinity(),
+                        attributes: vec![],
+                    })
+                }
+                Column::Commented(..) => unreachable!(),
+            }
+        }
+        SchemaIr {
+            name: self.name.to_owned(),
+            columns,
+            constraints,
+        }
+    }
+}
+
+/// A dependency-free representation of a `TableDef`, built by `TableDef::to_ir`.
+#[cfg(feature = "ir")]
+#[derive(Debug, PartialEq, Eq)]
+pub struct SchemaIr {
+    pub name: String,
+    pub columns: Vec<ColumnIr>,
+    pub constraints: Vec<ConstraintIr>,
+}
+
+#[cfg(feature = "ir")]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ColumnIr {
+    pub name: String,
+    /// The type as rendered into `CREATE TABLE`.
+    pub declared_type: String,
+    pub affinity: Affinity,
+    /// Each attribute as rendered into `CREATE TABLE`, e.g. `NOT NULL`.
+    pub attributes: Vec<String>,
+}
+
+#[cfg(feature = "ir")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstraintKind {
+    PrimaryKey,
+    Unique,
+    ForeignKey,
+    Other,
+}
+
+#[cfg(feature = "ir")]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConstraintIr {
+    pub kind: ConstraintKind,
+    /// The local columns of a primary key, unique or foreign key constraint.
+    pub columns: Vec<String>,
+    /// The referenced table and columns of a foreign key.
+    pub references: Option<(String, Vec<String>)>,
+    /// The constraint as rendered into `CREATE TABLE`.
+    pub sql: String,
+}
+
+impl Table for TableDef {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn columns(&self) -> &[Arc<Column>] {
+        &self.columns
+    }
+
+    fn indexes(&self) -> &[(String, Vec<Arc<Column>>)] {
+        &self.indexes
+    }
+
+    fn if_not_exists(&self) -> bool {
+        self.if_not_exists
+    }
+}
+
+pub enum Column {
+    Column {
+        name: String,
+        column_type: Type,
+        attributes: Option<Vec<Attribute>>,
+    },
+    Constraint(String),
+    /// A table-level `PRIMARY KEY` constraint built by `primary_key`.
+    PrimaryKey(Vec<String>),
+    /// A table-level `UNIQUE` constraint built by `unique`.
+    Unique(Vec<String>),
+    /// A `FOREIGN KEY` constraint built by `foreign_key`, rendered when the statement is created.
+    ForeignKey {
+        local: Vec<String>,
+        table: String,
+        references: Vec<String>,
+        /// The `ON DELETE`/`ON UPDATE` actions in declaration order. Each action is stored with
+        /// its trigger since a bare `ReferentialAction` cannot tell the two apart.
+        actions: Vec<(Trigger, ReferentialAction)>,
+        /// Renders `DEFERRABLE INITIALLY DEFERRED` after the actions.
+        deferred: bool,
+    },
+    /// A column definition rendered verbatim, e.g. `legacy_id INTEGER CHECK (legacy_id > 0)`.
+    ///
+    /// An escape hatch for definitions the typed API cannot express. The leading token is used
+    /// as the column name; nothing else is parsed or checked.
+    Raw(String),
+    /// A column or constraint with a comment emitted by `Table::create_sql_pretty`.
+    Commented(Arc<Column>, String),
+}
+
+impl Column {
+    pub fn name(&self) -> &str {
+        match self {
+            Column::Column { name, .. } => name,
+            Column::Raw(value) => value.split_whitespace().next().unwrap_or_default(),
+            Column::Commented(column, _) => column.name(),
+            _ => panic!(),
+        }
+    }
+
+    fn create_statement(&self) -> String {
+        self.create_statement_for(&Sqlite)
+    }
+
+    fn create_statement_for(&self, dialect: &dyn Dialect) -> String {
+        match self {
+            Column::Column {
+                name,
+                column_type,
+                attributes,
+            } => {
+                // The crate cannot tell keywords from identifiers in a raw or declared type, so it
+                // is kept as given.
+                let type_name = match column_type {
+                    Type::Raw(value) | Type::Declared(value, _) => value.to_owned(),
+                    _ => dialect.case_style().apply(&column_type.name()),
+                };
+                match attributes {
+                    Some(attributes) => format!(
+                        "{} {} {}",
+                        name,
+                        type_name,
+                        attributes
+                            .iter()
+                            .map(|data| data.name_for(dialect))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    ),
+                    None => format!("{} {}", name, type_name),
+                }
+            }
+            Column::Constraint(value) | Column::Raw(value) => value.into(),
+            Column::PrimaryKey(keys) => format!(
+                "{} ({})",
+                dialect.case_style().apply("PRIMARY KEY"),
+                keys.join(", ")
+            ),
+            Column::Unique(keys) => format!(
+                "{} ({})",
+                dialect.case_style().apply("UNIQUE"),
+                keys.join(", ")
+            ),
+            Column::ForeignKey {
+                local,
+                table,
+                references,
+                actions,
+                deferred,
+            } => {
+                let style = dialect.case_style();
+                let mut sql = format!(
+                    "{} ({}) {} {} ({})",
+                    style.apply("FOREIGN KEY"),
+                    local.join(", "),
+                    style.apply("REFERENCES"),
+                    table,
+                    references.join(", ")
+                );
+                for (trigger, action) in actions {
+                    sql.push_str(&format!(
+                        " {}",
+                        style.apply(&format!("{} {}", trigger, action))
+                    ));
+                }
+                if *deferred {
+                    sql.push_str(&format!(
+                        " {}",
+                        style.apply("DEFERRABLE INITIALLY DEFERRED")
+                    ));
+                }
+                sql
+            }
+            Column::Commented(column, _) => column.create_statement_for(dialect),
+        }
+    }
+
+    /// Attaches a comment that `Table::create_sql_pretty` emits as a trailing `-- comment`.
+    pub fn with_comment<T: Into<String>>(self: &Arc<Self>, comment: T) -> Arc<Column> {
+        Arc::new(Column::Commented(self.clone(), comment.into()))
+    }
+
+    /// Adds `NOT NULL` unless `nullable`, and unless the column already has it. A nullable
+    /// column is left as is since that is the SQLite default.
+    ///
+    /// Panics for a constraint unless `nullable`.
+    pub fn nullable(self: &Arc<Self>, nullable: bool) -> Arc<Column> {
+        if nullable
+            || self
+                .attributes()
+                .iter()
+                .any(|data| matches!(data, Attribute::NOT_NULL))
+        {
+            self.clone()
+        } else {
+            self.with_attribute(Attribute::NOT_NULL)
+        }
+    }
+
+    /// A copy of the column with `attribute` appended, keeping its comment if any.
+    ///
+    /// Panics for a constraint.
+    pub fn with_attribute(&self, attribute: Attribute) -> Arc<Column> {
+        match self {
+            Column::Column {
+                name,
+                column_type,
+                attributes,
+            } => {
+                let mut attributes = attributes.clone().unwrap_or_default();
+                attributes.push(attribute);
+                column(name.to_owned(), column_type.clone(), attributes)
+            }
+            Column::Commented(column, comment) => column
+                .with_attribute(attribute)
+                .with_comment(comment.to_owned()),
+            Column::Raw(value) => Arc::new(Column::Raw(format!("{} {}", value, attribute.name()))),
+            Column::Constraint(_)
+            | Column::PrimaryKey(_)
+            | Column::Unique(_)
+            | Column::ForeignKey { .. } => panic!(
+                "with_attribute called on table constraint {}",
+                self.create_statement()
+            ),
+        }
+    }
+
+    fn uncommented(&self) -> &Column {
+        match self {
+            Column::Commented(column, _) => column.uncommented(),
+            _ => self,
+        }
+    }
+
+    /// The local columns of a `primary_key`, `unique` or `foreign_key` constraint.
+    fn constraint_columns(&self) -> Vec<&str> {
+        let value = match self.uncommented() {
+            Column::Constraint(value) => value,
+            Column::PrimaryKey(keys)
+            | Column::Unique(keys)
+            | Column::ForeignKey { local: keys, .. } => {
+                return keys.iter().map(String::as_str).collect()
+            }
+            _ => return vec![],
+        };
+        let is_keyed = ["PRIMARY KEY (", "UNIQUE (", "FOREIGN KEY ("]
+            .iter()
+            .any(|data| value.starts_with(data));
+        match (is_keyed, value.find('('), value.find(')')) {
+            (true, Some(start), Some(end)) if start < end => value[start + 1..end]
+                .split(',')
+                .map(|data| data.trim())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// The table named by a `foreign_key` constraint or an inline `references`.
+    fn referenced_table(&self) -> Option<&str> {
+        let clause = match self.uncommented() {
+            Column::Constraint(value) if value.starts_with("FOREIGN KEY (") => {
+                value.split_once(") REFERENCES ")?.1
+            }
+            Column::Constraint(_) => return None,
+            Column::ForeignKey { table, .. } => return Some(table),
+            _ => self.attributes().iter().find_map(|data| match data {
+                Attribute::INLINE_REFERENCES(clause) => Some(clause.as_str()),
+                _ => None,
+            })?,
+        };
+        clause.split_once(" (").map(|(table, _)| table)
+    }
+
+    /// Checks that `AUTOINCREMENT` is only used on an inline `INTEGER PRIMARY KEY` and not
+    /// alongside a table-level `primary_key`.
+    fn autoincrement_error(&self, has_table_primary_key: bool) -> Option<SchemaError> {
+        let attributes = self.attributes();
+        let has = |f: fn(&Attribute) -> bool| attributes.iter().any(f);
+        if !has(|data| matches!(data, Attribute::AUTOINCREMENT)) {
+            return None;
+        }
+
+        let name = self.name().to_owned();
+        if has_table_primary_key {
+            Some(SchemaError::AutoincrementWithCompositeKey(name))
+        } else if !has(|data| matches!(data, Attribute::PRIMARY_KEY)) {
+            Some(SchemaError::AutoincrementWithoutPrimaryKey(name))
+        } else if !matches!(self.data_type(), Some(Type::INTEGER)) {
+            Some(SchemaError::InvalidAutoincrement(name))
+        } else {
+            None
+        }
+    }
+
+    fn is_constraint(&self) -> bool {
+        matches!(
+            self.uncommented(),
+            Column::Constraint(_)
+                | Column::PrimaryKey(_)
+                | Column::Unique(_)
+                | Column::ForeignKey { .. }
+        )
+    }
+
+    fn is_table_primary_key(&self) -> bool {
+        match self.uncommented() {
+            Column::PrimaryKey(_) => true,
+            Column::Constraint(value) => value.starts_with("PRIMARY KEY ("),
+            _ => false,
+        }
+    }
+
+    pub fn data_type(&self) -> Option<&Type> {
+        match self.uncommented() {
+            Column::Column { column_type, .. } => Some(column_type),
+            _ => None,
+        }
+    }
+
+    pub fn attributes(&self) -> &[Attribute] {
+        match self.uncommented() {
+            Column::Column { attributes, .. } => attributes.as_deref().unwrap_or_default(),
+            _ => &[],
+        }
+    }
+
+    fn check_add_column(&self) {
+        let (name, attributes) = match self.uncommented() {
+            Column::Column {
+                name, attributes, ..
+            } => (name, attributes.as_deref().unwrap_or_default()),
+            Column::Raw(_) => return,
+            column => panic!("cannot add a constraint: {}", column.create_statement()),
+        };
+
+        if attributes
+            .iter()
+            .any(|data| matches!(data, Attribute::PRIMARY_KEY | Attribute::UNIQUE))
+        {
+            panic!("cannot add a PRIMARY KEY or UNIQUE column: {}", name);
+        }
+
+        if attributes
+            .iter()
+            .any(|data| matches!(data, Attribute::NOT_NULL))
+            && !attributes
+                .iter()
+                .any(|data| matches!(data, Attribute::DEFAULT(_)))
+        {
+            panic!("cannot add a NOT NULL column without DEFAULT: {}", name);
+        }
+    }
+
+    pub fn create_add_sql(&self) -> String {
+        match self.uncommented() {
+            Column::Column { .. } | Column::Raw(_) => {
+                format!(
+                    "ALTER TABLE {} ADD {}",
+                    self.name(),
+                    self.create_statement()
+                )
+            }
+            _ => panic!(),
+        }
+    }
+}
+
+/// A table name with an optional alias. `Display` renders `name AS alias`, while foreign key
+/// references only use the name.
+///
+/// Two `TableName`s are equal if both the name and the alias are. Comparing with a `str` only
+/// looks at the name.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TableName {
+    name: String,
+    alias: Option<String>,
+}
+
+impl TableName {
+    pub fn alias<T: Into<String>>(mut self, alias: T) -> Self {
+        self.alias = Some(alias.into());
+        self
+    }
+}
+
+impl core::fmt::Display for TableName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.alias {
+            Some(alias) => write!(f, "{} AS {}", self.name, alias),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl PartialEq<str> for TableName {
+    fn eq(&self, other: &str) -> bool {
+        self.name == other
+    }
+}
+
+impl PartialEq<&str> for TableName {
+    fn eq(&self, other: &&str) -> bool {
+        self.name == *other
+    }
+}
+
+impl<T: Table + ?Sized> From<&T> for TableName {
+    fn from(value: &T) -> Self {
+        value.name().into()
+    }
+}
+
+impl From<String> for TableName {
+    fn from(value: String) -> Self {
+        Self {
+            name: value,
+            alias: None,
+        }
+    }
+}
+
+impl From<&str> for TableName {
+    fn from(value: &str) -> Self {
+        value.to_owned().into()
+    }
+}
+
+/// A column of an FTS5 virtual table, see `create_fts5_sql`.
+pub struct Fts5Column {
+    name: String,
+    unindexed: bool,
+}
+
+impl Fts5Column {
+    pub fn new<T: Into<String>>(name: T) -> Self {
+        Self {
+            name: name.into(),
+            unindexed: false,
+        }
+    }
+
+    /// Stores the value without adding it to the full-text index, so `MATCH` never finds it.
+    pub fn unindexed(mut self) -> Self {
+        self.unindexed = true;
+        self
+    }
+}
+
+/// `CREATE VIRTUAL TABLE name USING fts5(...)`. Requires SQLite built with FTS5.
+pub fn create_fts5_sql(name: &str, columns: &[Fts5Column]) -> String {
+    format!(
+        "CREATE VIRTUAL TABLE {} USING fts5({})",
+        name,
+        columns
+            .iter()
+            .map(|data| if data.unindexed {
+                format!("{} UNINDEXED", data.name)
+            } else {
+                data.name.to_owned()
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// A term of the `SELECT` list.
+pub enum SelectTerm {
+    Column(Arc<Column>),
+    /// Rendered verbatim, e.g. `COUNT(*)` or `users.name AS user_name`.
+    Expr(String),
+}
+
+impl SelectTerm {
+    fn create_statement(&self) -> String {
+        match self {
+            SelectTerm::Column(column) => column.name().to_owned(),
+            SelectTerm::Expr(expr) => expr.to_owned(),
+        }
+    }
+}
+
+impl From<Arc<Column>> for SelectTerm {
+    fn from(value: Arc<Column>) -> Self {
+        SelectTerm::Column(value)
+    }
+}
+
+impl From<&str> for SelectTerm {
+    fn from(value: &str) -> Self {
+        SelectTerm::Expr(value.into())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoinKind {
+    Inner,
+    Left,
+    Cross,
+}
+
+impl JoinKind {
+    fn name(&self) -> &str {
+        match self {
+            JoinKind::Inner => "INNER JOIN",
+            JoinKind::Left => "LEFT JOIN",
+            JoinKind::Cross => "CROSS JOIN",
+        }
+    }
+}
+
+/// A `SELECT` statement built by `select`.
+pub struct Select {
+    distinct: bool,
+    from: TableName,
+    terms: Vec<SelectTerm>,
+    joins: Vec<(JoinKind, TableName, String)>,
+    where_expr: Option<String>,
+    group_by: Vec<Arc<Column>>,
+    having: Option<String>,
+    order_by: Vec<IndexTerm>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+/// Starts a `SELECT` from `table`, selecting `*` until a term is added.
+pub fn select<T: Into<TableName>>(table: T) -> Select {
+    Select {
+        distinct: false,
+        from: table.into(),
+        terms: vec![],
+        joins: vec![],
+        where_expr: None,
+        group_by: vec![],
+        having: None,
+        order_by: vec![],
+        limit: None,
+        offset: None,
+    }
+}
+
+impl Select {
+    /// Renders `SELECT DISTINCT`.
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
+        self
+    }
+
+    pub fn term<T: Into<SelectTerm>>(mut self, term: T) -> Self {
+        self.terms.push(term.into());
+        self
+    }
+
+    /// Joins `other` on the verbatim `on_expr`. An empty `on_expr` omits `ON`, as for
+    /// `JoinKind::Cross`.
+    pub fn join<T: Into<TableName>>(mut self, kind: JoinKind, other: T, on_expr: &str) -> Self {
+        self.joins.push((kind, other.into(), on_expr.to_owned()));
+        self
+    }
+
+    /// A verbatim `WHERE` expression.
+    pub fn where_expr<T: Into<String>>(mut self, expr: T) -> Self {
+        self.where_expr = Some(expr.into());
+        self
+    }
+
+    pub fn group_by(mut self, columns: &[Arc<Column>]) -> Self {
+        self.group_by.extend(columns.iter().cloned());
+        self
+    }
+
+    /// A verbatim `HAVING` expression, e.g. `COUNT(*) > 1`.
+    pub fn having<T: Into<String>>(mut self, expr: T) -> Self {
+        self.having = Some(expr.into());
+        self
+    }
+
+    pub fn order_by(mut self, terms: &[(Arc<Column>, SortOrder)]) -> Self {
+        self.order_by.extend(
+            terms
+                .iter()
+                .map(|(column, order)| IndexTerm::new(column.clone()).order(*order)),
+        );
+        self
+    }
+
+    /// `ORDER BY` terms that may carry a collation, rendered as `name COLLATE NOCASE ASC`.
+    pub fn order_by_terms<T: Into<Vec<IndexTerm>>>(mut self, terms: T) -> Self {
+        self.order_by.extend(terms.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// `OFFSET` needs a `LIMIT` in SQLite, so an offset alone is rendered as `LIMIT -1 OFFSET n`.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn sql(&self) -> String {
+        let terms = if self.terms.is_empty() {
+            "*".to_owned()
+        } else {
+            self.terms
+                .iter()
+                .map(|data| data.create_statement())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut sql = format!(
+            "SELECT {}{} FROM {}",
+            if self.distinct { "DISTINCT " } else { "" },
+            terms,
+            self.from
+        );
+        for (kind, other, on_expr) in &self.joins {
+            sql.push_str(&format!(" {} {}", kind.name(), other));
+            if !on_expr.is_empty() {
+                sql.push_str(&format!(" ON {}", on_expr));
+            }
+        }
+        if let Some(expr) = &self.where_expr {
+            sql.push_str(&format!(" WHERE {}", expr));
+        }
+        if !self.group_by.is_empty() {
+            sql.push_str(&format!(
+                " GROUP BY {}",
+                self.group_by
+                    .iter()
+                    .map(|data| data.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if let Some(expr) = &self.having {
+            sql.push_str(&format!(" HAVING {}", expr));
+        }
+        if !self.order_by.is_empty() {
+            sql.push_str(&format!(
+                " ORDER BY {}",
+                self.order_by
+                    .iter()
+                    .map(|data| data.create_statement())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        match (self.limit, self.offset) {
+            (Some(limit), Some(offset)) => {
+                sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset))
+            }
+            (Some(limit), None) => sql.push_str(&format!(" LIMIT {}", limit)),
+            (None, Some(offset)) => sql.push_str(&format!(" LIMIT -1 OFFSET {}", offset)),
+            (None, None) => {}
+        }
+        sql
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Attribute::*;
+    use crate::ForeignKeyAttribute::*;
+    use crate::Type::*;
+    use crate::*;
+    use rusqlite::{named_params, params};
+    use std::sync::Arc;
+
+    #[test]
+    fn empty_arr() {
+        assert_eq!(
+            (&[][..] as &[&str])
+                .iter()
+                .map(|data| data.to_owned())
+                .collect::<Vec<_>>()
+                .join(" "),
+            String::new()
+        )
+    }
+
+    #[test]
+    fn all_types() {
+        let integer = column("integer", INTEGER, []);
+        let int = column("int", INT, []);
+        let tinyint = column("tinyint", TINYINT, []);
+        let smallint = column("smallint", SMALLINT, []);
+        let mediumint = column("mediumint", MEDIUMINT, []);
+        let bigint = column("bigint", BIGINT, []);
+        let unsigned_big_int = column("unsigned_big_int", UNSIGNED_BIG_INT, []);
+        let int2 = column("int2", INT2, []);
+        let int8 = column("int8", INT8, []);
+        let text = column("text", TEXT, []);
+        let clob = column("clob", CLOB, []);
+        let blob = column("blob", BLOB, []);
+        let real = column("real", REAL, []);
+        let double = column("double", DOUBLE, []);
+        let double_precision = column("double_precision", DOUBLE_PRECISION, []);
+        let float = column("float", FLOAT, []);
+        let numeric = column("numeric", NUMERIC, []);
+        let boolean = column("boolean", BOOLEAN, []);
+        let date = column("date", DATE, []);
+        let datetime = column("datetime", DATETIME, []);
+        let json = column("json", JSON, []);
+
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let sql = MyTable {
+            columns: vec![
+                integer.clone(),
+                int.clone(),
+                tinyint.clone(),
+                smallint.clone(),
+                mediumint.clone(),
+                bigint.clone(),
+                unsigned_big_int.clone(),
+                int2.clone(),
+                int8.clone(),
+                text.clone(),
+                clob.clone(),
+                blob.clone(),
+                real.clone(),
+                double.clone(),
+                double_precision.clone(),
+                float.clone(),
+                numeric.clone(),
+                boolean.clone(),
+                date.clone(),
+                datetime.clone(),
+                json.clone(),
+            ],
+        }
+        .create_sql();
+
+        assert_eq!(
+            sql,
+            format!("CREATE TABLE my_table ({} INTEGER, {} INT, {} TINYINT, {} SMALLINT, {} MEDIUMINT, {} BIGINT, {} UNSIGNED BIG INT, {} INT2, {} INT8, {} TEXT, {} CLOB, {} BLOB, {} REAL, {} DOUBLE, {} DOUBLE PRECISION, {} FLOAT, {} NUMERIC, {} BOOLEAN, {} DATE, {} DATETIME, {} JSON)",
+                    integer.name(), int.name(), tinyint.name(), smallint.name(), mediumint.name(), bigint.name(), unsigned_big_int.name(), int2.name(), int8.name(), text.name(), clob.name(), blob.name(), real.name(), double.name(), double_precision.name(), float.name(), numeric.name(), boolean.name(), date.name(), datetime.name(), json.name()
+            ));
+
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&sql, params![])
+            .unwrap();
+    }
+
+    #[test]
+    fn unique_constraint() {
+        struct UniqueTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for UniqueTable {
+            fn name(&self) -> &str {
+                "unique_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col1 = column("val1", TEXT, []);
+        let col2 = column("val2", TEXT, []);
+        let sql = UniqueTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]),
+                col1.clone(),
+                col2.clone(),
+                unique([col1, col2]),
+            ],
+        }
+        .create_sql();
+
+        assert_eq!(sql, "CREATE TABLE unique_table (id INTEGER PRIMARY KEY NOT NULL, val1 TEXT, val2 TEXT, UNIQUE (val1, val2))");
+
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&sql, params![])
+            .unwrap();
+    }
+
+    #[test]
+    fn primary_constraint() {
+        struct PrimaryTable {
+            column: Vec<Arc<Column>>,
+        }
+
+        impl Table for PrimaryTable {
+            fn name(&self) -> &str {
+                "primary_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.column
+            }
+        }
+
+        let col1 = column("col1", TEXT, []);
+        let col2 = column("col2", TEXT, []);
+        let sql = PrimaryTable {
+            column: vec![col1.clone(), col2.clone(), primary_key([col1, col2])],
+        }
+        .create_sql();
+
+        assert_eq!(
+            sql,
+            "CREATE TABLE primary_table (col1 TEXT, col2 TEXT, PRIMARY KEY (col1, col2))"
+        );
+
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&sql, params![])
+            .unwrap();
+    }
+
+    #[test]
+    fn foreignkey() {
+        struct MyTable {
+            hoge_column: Arc<Column>,
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl MyTable {
+            fn new() -> Self {
+                let hoge_column = column("hoge", TEXT, []);
+                Self {
+                    hoge_column: hoge_column.clone(),
+                    columns: vec![
+                        column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]),
+                        column("val", TEXT, [DEFAULT("def".into())]),
+                        hoge_column.clone(),
+                    ],
+                }
+            }
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let my_table = MyTable::new();
+
+        let mytable_sql = MyTable::new().create_sql();
+        assert_eq!(
+            mytable_sql,
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY NOT NULL, val TEXT DEFAULT 'def', hoge TEXT)"
+        );
+
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&mytable_sql, params![])
+            .unwrap();
+
+        struct ForeignTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl ForeignTable {
+            fn new(my_table: &MyTable) -> Self {
+                let rc_column = column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+                Self {
+                    columns: vec![
+                        rc_column.clone(),
+                        foreign_key(
+                            rc_column.clone(),
+                            REFERENCES,
+                            my_table,
+                            my_table.hoge_column.clone(),
+                            [],
+                        ),
+                    ],
+                }
+            }
+        }
+
+        impl Table for ForeignTable {
+            fn name(&self) -> &str {
+                "foreign_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let foreigntable_sql = ForeignTable::new(&my_table).create_sql();
+        assert_eq!(
+            foreigntable_sql,
+            "CREATE TABLE foreign_table (id INTEGER PRIMARY KEY NOT NULL, FOREIGN KEY (id) REFERENCES my_table (hoge))");
+
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&foreigntable_sql, params![])
+            .unwrap();
+    }
+
+    #[test]
+    fn index() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+            indexes: Vec<(String, Vec<Arc<Column>>)>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+
+            fn indexes(&self) -> &[(String, Vec<Arc<Column>>)] {
+                &self.indexes
+            }
+        }
+
+        let col1 = column("col1", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+        let col2 = column("col2", TEXT, [NOT_NULL]);
+        let col3 = column("col3", TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![col1, col2.clone(), col3.clone()],
+            indexes: vec![
+                ("col2_index".into(), vec![col2.clone()]),
+                ("col2_3_index".into(), vec![col2, col3]),
+            ],
+        };
+
+        let indexes = table.create_index();
+        assert_eq!(2, indexes.len());
+        assert_eq!("CREATE INDEX col2_index ON my_table (col2)", indexes[0]);
+        assert_eq!(
+            "CREATE INDEX col2_3_index ON my_table (col2,col3)",
+            indexes[1]
+        );
+    }
+
+    #[test]
+    fn dyn_table() {
+        struct MyTable {
+            name: &'static str,
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                self.name
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let parent_id = column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+        let parent: Arc<dyn Table> = Arc::new(MyTable {
+            name: "parent_table",
+            columns: vec![parent_id.clone()],
+        });
+
+        let child_id = column("parent_id", INTEGER, [NOT_NULL]);
+        let tables: Vec<Box<dyn Table>> = vec![
+            Box::new(parent.clone()),
+            Box::new(MyTable {
+                name: "child_table",
+                columns: vec![
+                    child_id.clone(),
+                    foreign_key(child_id, REFERENCES, &parent, parent_id, []),
+                ],
+            }),
+        ];
+
+        let sqls = tables
+            .iter()
+            .map(|data| data.create_sql())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sqls,
+            [
+                "CREATE TABLE parent_table (id INTEGER PRIMARY KEY NOT NULL)",
+                "CREATE TABLE child_table (parent_id INTEGER NOT NULL, FOREIGN KEY (parent_id) REFERENCES parent_table (id))",
+            ]
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for sql in &sqls {
+            conn.execute(sql, params![]).unwrap();
+        }
+    }
+
+    #[test]
+    fn add_columns() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![column("id", INTEGER, [PRIMARY_KEY, NOT_NULL])],
+        };
+
+        let sqls = table.add_columns_sql(&[
+            column("col1", TEXT, []),
+            column("col2", INTEGER, [NOT_NULL, DEFAULT("0".into())]),
+            column("col3", REAL, []),
+        ]);
+        assert_eq!(
+            sqls,
+            [
+                "ALTER TABLE my_table ADD COLUMN col1 TEXT",
+                "ALTER TABLE my_table ADD COLUMN col2 INTEGER NOT NULL DEFAULT '0'",
+                "ALTER TABLE my_table ADD COLUMN col3 REAL",
+            ]
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute("INSERT INTO my_table (id) VALUES (1)", params![])
+            .unwrap();
+        for sql in &sqls {
+            conn.execute(sql, params![]).unwrap();
+        }
+        let col2: i64 = conn
+            .query_row("SELECT col2 FROM my_table", params![], |row| row.get(0))
+            .unwrap();
+        assert_eq!(0, col2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn add_columns_not_null_without_default() {
+        struct MyTable;
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &[]
+            }
+        }
+
+        MyTable.add_columns_sql(&[column("col1", TEXT, [NOT_NULL])]);
+    }
+
+    #[test]
+    fn index_terms() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+            index_terms: Vec<(String, Vec<IndexTerm>)>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+
+            fn index_terms(&self) -> &[(String, Vec<IndexTerm>)] {
+                &self.index_terms
+            }
+        }
+
+        let col1 = column("col1", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+        let col2 = column("col2", TEXT, [NOT_NULL]);
+        let col3 = column("col3", TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![col1, col2.clone(), col3.clone()],
+            index_terms: vec![
+                (
+                    "col2_nocase_index".into(),
+                    vec![IndexTerm::new(col2.clone()).collate(CollationName::NOCASE)],
+                ),
+                (
+                    "col2_3_index".into(),
+                    vec![
+                        IndexTerm::new(col2)
+                            .collate(CollationName::NOCASE)
+                            .order(SortOrder::DESC),
+                        col3.into(),
+                    ],
+                ),
+            ],
+        };
+
+        let indexes = table.create_index();
+        assert_eq!(
+            indexes,
+            [
+                "CREATE INDEX col2_nocase_index ON my_table (col2 COLLATE NOCASE)",
+                "CREATE INDEX col2_3_index ON my_table (col2 COLLATE NOCASE DESC,col3)",
+            ]
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        for sql in &indexes {
+            conn.execute(sql, params![]).unwrap();
+        }
+    }
+
+    #[test]
+    fn validate() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT]),
+                column("val", TEXT, []),
+            ],
+        };
+        assert_eq!(Ok(()), table.validate());
+
+        let table = MyTable {
+            columns: vec![
+                column("id", TEXT, [PRIMARY_KEY, AUTOINCREMENT]),
+                column("val", TEXT, []),
+                column("VAL", TEXT, []),
+                column("note", TEXT, [NOT_NULL, DEFAULT(DefaultValue::Null)]),
+            ],
+        };
+        assert_eq!(
+            Err(vec![
+                SchemaError::InvalidAutoincrement("id".into()),
+                SchemaError::DuplicateColumn("VAL".into()),
+                SchemaError::NotNullDefaultNull("note".into()),
+            ]),
+            table.validate()
+        );
+
+        let table = MyTable { columns: vec![] };
+        assert_eq!(Err(vec![SchemaError::NoColumns]), table.validate());
+    }
+
+    #[test]
+    fn try_create_sql_no_columns() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable { columns: vec![] };
+        assert_eq!(Err(SchemaError::NoColumns), table.try_create_sql());
+
+        let col1 = column("col1", TEXT, []);
+        let table = MyTable {
+            columns: vec![unique([col1])],
+        };
+        assert_eq!(Err(SchemaError::NoColumns), table.try_create_sql());
+
+        let col1 = column("col1", TEXT, []);
+        let table = MyTable {
+            columns: vec![col1.clone(), unique([col1])],
+        };
+        assert_eq!(
+            Ok("CREATE TABLE my_table (col1 TEXT, UNIQUE (col1))".into()),
+            table.try_create_sql()
+        );
+    }
+
+    #[test]
+    fn insert_named() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let id = column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+        let col1 = column("col1", TEXT, []);
+        let col2 = column("col2", TEXT, []);
+        let table = MyTable {
+            columns: vec![id, col1.clone(), col2.clone()],
+        };
+
+        let sql = table.insert_named_sql(&[col1, col2]);
+        assert_eq!(
+            sql,
+            "INSERT INTO my_table (col1, col2) VALUES (:col1, :col2)"
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(&sql, named_params! {":col1": "a", ":col2": "b"})
+            .unwrap();
+        let row: (String, String) = conn
+            .query_row("SELECT col1, col2 FROM my_table", params![], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(("a".to_owned(), "b".to_owned()), row);
+    }
+
+    #[test]
+    fn partial_unique() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+            partial_uniques: Vec<(String, Vec<Arc<Column>>, String)>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+
+            fn partial_uniques(&self) -> &[(String, Vec<Arc<Column>>, String)] {
+                &self.partial_uniques
+            }
+        }
+
+        let id = column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+        let email = column("email", TEXT, [NOT_NULL]);
+        let deleted = column("deleted", INTEGER, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![id, email.clone(), deleted],
+            partial_uniques: vec![(
+                "email_active_index".into(),
+                vec![email],
+                "deleted = 0".into(),
+            )],
+        };
+
+        let sqls = table.create_all_sql();
+        assert_eq!(
+            sqls,
+            [
+                "CREATE TABLE my_table (id INTEGER PRIMARY KEY NOT NULL, email TEXT NOT NULL, deleted INTEGER NOT NULL)",
+                "CREATE UNIQUE INDEX email_active_index ON my_table (email) WHERE deleted = 0",
+            ]
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for sql in &sqls {
+            conn.execute(sql, params![]).unwrap();
+        }
+        let insert = "INSERT INTO my_table (email, deleted) VALUES (?, ?)";
+        conn.execute(insert, params!["a@example.com", 1]).unwrap();
+        conn.execute(insert, params!["a@example.com", 1]).unwrap();
+        conn.execute(insert, params!["a@example.com", 0]).unwrap();
+        assert!(conn.execute(insert, params!["a@example.com", 0]).is_err());
+    }
+
+    #[test]
+    fn type_from_declared() {
+        for data in [
+            INTEGER,
+            INT,
+            TINYINT,
+            SMALLINT,
+            MEDIUMINT,
+            BIGINT,
+            UNSIGNED_BIG_INT,
+            INT2,
+            INT8,
+            TEXT,
+            CLOB,
+            BLOB,
+            REAL,
+            DOUBLE,
+            DOUBLE_PRECISION,
+            FLOAT,
+            NUMERIC,
+            BOOLEAN,
+            DATE,
+            DATETIME,
+            JSON,
+            ANY,
+        ] {
+            assert_eq!(data, Type::from_declared(&data.name()));
+        }
+
+        assert_eq!(BOOLEAN, Type::from_declared("boolean"));
+        assert_eq!(UNSIGNED_BIG_INT, Type::from_declared("unsigned  big\tint"));
+        assert_eq!(VARCHAR(3), Type::from_declared("VARCHAR(3)"));
+        assert_eq!(DECIMAL(10, 2), Type::from_declared("decimal ( 10 ,2 )"));
+        assert_eq!(CHAR(2), Type::from_declared("char(\t2)"));
+        assert_eq!(Raw("VARCHAR(x)".into()), Type::from_declared("VARCHAR(x)"));
+        assert_eq!(
+            Raw("DECIMAL(1, 2, 3)".into()),
+            Type::from_declared("DECIMAL(1, 2, 3)")
+        );
+        for data in [
+            VARCHAR(255),
+            CHAR(2),
+            DECIMAL(10, 2),
+            Type::decimal(8),
+            BINARY(16),
+            VARBINARY(64),
+        ] {
+            assert_eq!(data, Type::from_declared(&data.name()));
+        }
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE my_table (boolean BOOLEAN)", params![])
+            .unwrap();
+        let declared: String = conn
+            .query_row(
+                "SELECT type FROM pragma_table_info('my_table')",
+                params![],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(BOOLEAN, Type::from_declared(&declared));
+
+        conn.execute(
+            "CREATE TABLE other_table (name varchar( 16 ), price DECIMAL(10,2))",
+            params![],
+        )
+        .unwrap();
+        let mut statement = conn
+            .prepare("SELECT type FROM pragma_table_info('other_table')")
+            .unwrap();
+        let declared = statement
+            .query_map(params![], |row| row.get::<_, String>(0))
+            .unwrap()
+            .map(|data| Type::from_declared(&data.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![VARCHAR(16), DECIMAL(10, 2)], declared);
+        assert_eq!(Some(16), declared[0].length());
+        assert_eq!(Some((10, 2)), declared[1].precision_scale());
+    }
+
+    #[test]
+    fn placeholders_arity() {
+        assert_eq!("", placeholders(0));
+        assert_eq!("?", placeholders(1));
+        assert_eq!("?, ?, ?, ?, ?", placeholders(5));
+    }
+
+    #[test]
+    fn insert() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let id = column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+        let col1 = column("col1", TEXT, []);
+        let table = MyTable {
+            columns: vec![id.clone(), col1.clone()],
+        };
+
+        let sql = table.insert_sql(&[id, col1]);
+        assert_eq!(sql, "INSERT INTO my_table (id, col1) VALUES (?, ?)");
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(&sql, params![1, "a"]).unwrap();
+    }
+
+    #[test]
+    fn insert_defaults() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let id = column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+        let col1 = column("col1", TEXT, [NOT_NULL]);
+        let col2 = column("col2", TEXT, [NOT_NULL, DEFAULT("def".into())]);
+        let col3 = column("col3", TEXT, []);
+        let table = MyTable {
+            columns: vec![id, col1.clone(), col2.clone(), col3],
+        };
+
+        let sql = table.try_insert_sql(&[col1]).unwrap();
+        assert_eq!(sql, "INSERT INTO my_table (col1) VALUES (?)");
+        assert_eq!(
+            Err(SchemaError::MissingValue("col1".into())),
+            table.try_insert_sql(&[col2])
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(&sql, params!["a"]).unwrap();
+        let row: (i64, String, Option<String>) = conn
+            .query_row("SELECT id, col2, col3 FROM my_table", params![], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!((1, "def".to_owned(), None), row);
+    }
+
+    #[test]
+    fn attach_database() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "other.my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let attach = attach_database_sql(":memory:", "other");
+        assert_eq!(attach, "ATTACH DATABASE ':memory:' AS other");
+        assert_eq!(
+            "ATTACH DATABASE 'it''s.db' AS other",
+            attach_database_sql("it's.db", "other")
+        );
+        let detach = detach_database_sql("other");
+        assert_eq!(detach, "DETACH DATABASE other");
+
+        let table = MyTable {
+            columns: vec![column("id", INTEGER, [PRIMARY_KEY, NOT_NULL])],
+        };
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&attach, params![]).unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute("INSERT INTO other.my_table (id) VALUES (1)", params![])
+            .unwrap();
+        conn.execute(&detach, params![]).unwrap();
+        assert!(conn
+            .execute("INSERT INTO other.my_table (id) VALUES (2)", params![])
+            .is_err());
+    }
+
+    #[test]
+    fn create_sql_sorted() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col_b = column("b", TEXT, []);
+        let col_c = column("c", TEXT, []);
+        let table = MyTable {
+            columns: vec![
+                col_c.clone(),
+                column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]),
+                col_b.clone(),
+                unique([col_b, col_c]),
+                column("a", TEXT, []),
+            ],
+        };
+
+        assert_eq!(
+            table.create_sql(),
+            table.create_sql_sorted(ColumnOrder::default())
+        );
+        assert_eq!(
+            "CREATE TABLE my_table (c TEXT, id INTEGER PRIMARY KEY NOT NULL, b TEXT, UNIQUE (b, c), a TEXT)",
+            table.create_sql_sorted(ColumnOrder::Declared)
+        );
+        assert_eq!(
+            "CREATE TABLE my_table (a TEXT, b TEXT, c TEXT, id INTEGER PRIMARY KEY NOT NULL, UNIQUE (b, c))",
+            table.create_sql_sorted(ColumnOrder::Alphabetical)
+        );
+
+        let sql = table.create_sql_sorted(ColumnOrder::KeysFirst);
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY NOT NULL, c TEXT, b TEXT, a TEXT, UNIQUE (b, c))",
+            sql
+        );
+
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&sql, params![])
+            .unwrap();
+    }
+
+    #[test]
+    fn boolean_check() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![boolean_column("flag", [NOT_NULL])],
+        };
+
+        let sql = table.create_sql();
+        assert_eq!(
+            sql,
+            "CREATE TABLE my_table (flag BOOLEAN NOT NULL CHECK (flag IN (0, 1)))"
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        let insert = "INSERT INTO my_table (flag) VALUES (?)";
+        conn.execute(insert, params![0]).unwrap();
+        conn.execute(insert, params![1]).unwrap();
+        assert!(conn.execute(insert, params![2]).is_err());
+    }
+
+    #[test]
+    fn default_literal() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]),
+                column("text", TEXT, [DEFAULT("CURRENT_TIMESTAMP".into())]),
+                column(
+                    "raw",
+                    TEXT,
+                    [DEFAULT(DefaultValue::RawLiteral(
+                        "CURRENT_TIMESTAMP".into(),
+                    ))],
+                ),
+                column("quote", TEXT, [DEFAULT("\"foo\"".into())]),
+            ],
+        };
+
+        let sql = table.create_sql();
+        assert_eq!(
+            sql,
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY NOT NULL, text TEXT DEFAULT 'CURRENT_TIMESTAMP', raw TEXT DEFAULT CURRENT_TIMESTAMP, quote TEXT DEFAULT '\"foo\"')"
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute("INSERT INTO my_table (id) VALUES (1)", params![])
+            .unwrap();
+        let row: (String, String, String) = conn
+            .query_row("SELECT text, raw, quote FROM my_table", params![], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!("CURRENT_TIMESTAMP", row.0);
+        assert_ne!("CURRENT_TIMESTAMP", row.1);
+        assert_eq!("\"foo\"", row.2);
+    }
+
+    #[test]
+    fn create_parts() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col1 = column("col1", TEXT, []);
+        let col2 = column("col2", TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![
+                col1.clone(),
+                col2.clone(),
+                primary_key([col1.clone()]),
+                unique([col1, col2]),
+            ],
+        };
+
+        let parts = table.create_parts();
+        assert_eq!(
+            CreateParts {
+                name: "my_table".into(),
+                columns: vec!["col1 TEXT".into(), "col2 TEXT NOT NULL".into()],
+                constraints: vec!["PRIMARY KEY (col1)".into(), "UNIQUE (col1, col2)".into()],
+            },
+            parts
+        );
+        assert_eq!(
+            table.create_sql(),
+            format!(
+                "CREATE TABLE {} ({})",
+                parts.name,
+                [parts.columns, parts.constraints].concat().join(", ")
+            )
+        );
+    }
+
+    #[test]
+    fn upsert() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col1 = column("col1", TEXT, [NOT_NULL]);
+        let col2 = column("col2", TEXT, [NOT_NULL]);
+        let val = column("val", INTEGER, []);
+        let conflict = vec![col1.clone(), col2.clone()];
+        let table = MyTable {
+            columns: vec![col1.clone(), col2.clone(), val.clone(), unique(&conflict)],
+        };
+
+        let sql = table.upsert_sql(&[col1.clone(), col2.clone(), val], &conflict);
+        assert_eq!(
+            sql,
+            "INSERT INTO my_table (col1, col2, val) VALUES (?, ?, ?) ON CONFLICT (col1, col2) DO UPDATE SET val = excluded.val"
+        );
+        assert_eq!(
+            "INSERT INTO my_table (col1, col2) VALUES (?, ?) ON CONFLICT (col1, col2) DO NOTHING",
+            table.upsert_sql(&conflict, &conflict)
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(&sql, params!["a", "b", 1]).unwrap();
+        conn.execute(&sql, params!["a", "c", 2]).unwrap();
+        conn.execute(&sql, params!["a", "b", 3]).unwrap();
+        let rows = conn
+            .prepare("SELECT col2, val FROM my_table ORDER BY col2")
+            .unwrap()
+            .query_map(params![], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<Vec<(String, i64)>, _>>()
+            .unwrap();
+        assert_eq!(vec![("b".to_owned(), 3), ("c".to_owned(), 2)], rows);
+    }
+
+    #[test]
+    fn terminated() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+            indexes: Vec<(String, Vec<Arc<Column>>)>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+
+            fn indexes(&self) -> &[(String, Vec<Arc<Column>>)] {
+                &self.indexes
+            }
+        }
+
+        let col1 = column("col1", TEXT, [DEFAULT("a;b".into())]);
+        let table = MyTable {
+            columns: vec![col1.clone()],
+            indexes: vec![("col1_index".into(), vec![col1])],
+        };
+
+        let sql = table.create_sql_terminated();
+        assert_eq!(sql, "CREATE TABLE my_table (col1 TEXT DEFAULT 'a;b');");
+        assert_eq!(format!("{};", table.create_sql()), sql);
+        assert!(!table.create_sql().ends_with(';'));
+
+        let indexes = table.create_index_terminated();
+        assert_eq!(indexes, ["CREATE INDEX col1_index ON my_table (col1);"]);
+        assert_eq!(1, indexes[0].matches(';').count());
+
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute_batch(&[sql, indexes.concat()].concat())
+            .unwrap();
+    }
+
+    #[test]
+    fn collation_name() {
+        assert_eq!(Ok(CollationName::NOCASE), "NOCASE".parse());
+        assert!("nocase".parse::<CollationName>().unwrap().is_builtin());
+
+        let custom = "my_collation1".parse::<CollationName>().unwrap();
+        assert!(!custom.is_builtin());
+
+        for data in ["", "1abc", "no case", "\"quoted\"", "it's"] {
+            assert_eq!(
+                Err(SchemaError::InvalidCollationName(data.into())),
+                data.parse::<CollationName>()
+            );
+        }
+
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("col1", TEXT, [COLLATE(CollationName::NOCASE), UNIQUE]),
+                column("col2", TEXT, [COLLATE(CollationName::RTRIM)]),
+            ],
+        };
+
+        let sql = table.create_sql();
+        assert_eq!(
+            sql,
+            "CREATE TABLE my_table (col1 TEXT COLLATE NOCASE UNIQUE, col2 TEXT COLLATE RTRIM)"
+        );
+        assert_eq!("COLLATE my_collation1", COLLATE(custom).name());
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute("INSERT INTO my_table (col1) VALUES ('a')", params![])
+            .unwrap();
+        assert!(conn
+            .execute("INSERT INTO my_table (col1) VALUES ('A')", params![])
+            .is_err());
+    }
+
+    #[test]
+    fn type_affinity() {
+        assert_eq!(Affinity::INTEGER, UNSIGNED_BIG_INT.affinity());
+        assert_eq!(Affinity::TEXT, CLOB.affinity());
+        assert_eq!(Affinity::BLOB, BLOB.affinity());
+        assert_eq!(Affinity::REAL, DOUBLE_PRECISION.affinity());
+        assert_eq!(Affinity::NUMERIC, BOOLEAN.affinity());
+        assert_eq!(Affinity::TEXT, Raw("VARCHAR(255)".into()).affinity());
+        assert_eq!(Affinity::INTEGER, Raw("POINT".into()).affinity());
+        assert_eq!(Affinity::NUMERIC, Raw("DECIMAL(10, 2)".into()).affinity());
+
+        assert_eq!(Affinity::BLOB, ANY.affinity());
+        assert!(!ANY.is_integer());
+        assert!(!ANY.is_text());
+        assert!(!ANY.is_real());
+        assert!(!ANY.is_numeric());
+        assert!(INT8.is_integer());
+        assert!(TEXT.is_text());
+        assert!(FLOAT.is_real());
+        assert!(DATE.is_numeric());
+    }
+
+    #[test]
+    fn any_strict() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]),
+                column("val", ANY, []),
+            ],
+        };
+
+        let sql = format!("{} STRICT", table.create_sql());
+        assert_eq!(
+            sql,
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY NOT NULL, val ANY) STRICT"
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute("INSERT INTO my_table (val) VALUES ('123')", params![])
+            .unwrap();
+        let value_type: String = conn
+            .query_row("SELECT typeof(val) FROM my_table", params![], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!("text", value_type);
+    }
+
+    #[test]
+    fn constraint_comment() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let id = column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+        let email = column("email", TEXT, [NOT_NULL]);
+        let tenant = column("tenant", TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![
+                id,
+                email.clone(),
+                tenant.clone(),
+                unique([email, tenant]).with_comment("login uniqueness"),
+            ],
+        };
+
+        let pretty = table.create_sql_pretty();
+        assert_eq!(
+            pretty,
+            "CREATE TABLE my_table (
+    id INTEGER PRIMARY KEY NOT NULL,
+    email TEXT NOT NULL,
+    tenant TEXT NOT NULL,
+    UNIQUE (email, tenant) -- login uniqueness
+)"
+        );
+
+        let compact = table.create_sql();
+        assert_eq!(
+            compact,
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY NOT NULL, email TEXT NOT NULL, tenant TEXT NOT NULL, UNIQUE (email, tenant))"
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&pretty, params![]).unwrap();
+        conn.execute(
+            "INSERT INTO my_table (email, tenant) VALUES ('a', 'b')",
+            params![],
+        )
+        .unwrap();
+        assert!(conn
+            .execute(
+                "INSERT INTO my_table (email, tenant) VALUES ('a', 'b')",
+                params![]
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn attribute_from_str() {
+        for data in [
+            PRIMARY_KEY,
+            ASC,
+            DESC,
+            UNIQUE,
+            NOT_NULL,
+            AUTOINCREMENT,
+            DEFAULT("def".into()),
+            DEFAULT("it's".into()),
+            DEFAULT(DefaultValue::RawLiteral("CURRENT_TIMESTAMP".into())),
+            DEFAULT(DefaultValue::RawLiteral("'a' || 'b'".into())),
+            DEFAULT(DefaultValue::Integer(-1)),
+            DEFAULT(DefaultValue::Real(1.5)),
+            DEFAULT(DefaultValue::Real(f64::NEG_INFINITY)),
+            CHECK("val > 0".into()),
+            COLLATE(CollationName::NOCASE),
+        ] {
+            assert_eq!(Ok(&data), data.name().parse::<Attribute>().as_ref());
+        }
+
+        assert_eq!(Ok(NOT_NULL), "  not   null ".parse());
+        assert_eq!(Ok(PRIMARY_KEY), "Primary Key".parse());
+        assert_eq!(Ok(DEFAULT("x".into())), "default 'x'".parse());
+        assert_eq!(Ok(DEFAULT(DefaultValue::Integer(-1))), "DEFAULT -1".parse());
+        assert_eq!(Ok(DEFAULT(DefaultValue::Real(1.5))), "DEFAULT 1.5".parse());
+        assert_eq!(
+            Ok(DEFAULT(DefaultValue::Real(f64::INFINITY))),
+            "DEFAULT 9e999".parse()
+        );
+        assert_eq!(
+            Ok(DEFAULT(DefaultValue::Bool(true))),
+            "DEFAULT TRUE".parse()
+        );
+        assert_eq!(
+            Ok(DEFAULT(DefaultValue::Bool(false))),
+            "default false".parse()
+        );
+        assert_eq!(Ok(DEFAULT(DefaultValue::Null)), "DEFAULT NULL".parse());
+        assert_eq!(
+            Ok(DEFAULT(DefaultValue::RawLiteral("inf".into()))),
+            "DEFAULT inf".parse()
+        );
+        assert_eq!(
+            Err(ParseAttributeError("NOT NUL".into())),
+            "NOT NUL".parse::<Attribute>()
+        );
+        assert!("DEFAULT".parse::<Attribute>().is_err());
+        assert!("COLLATE no case".parse::<Attribute>().is_err());
+    }
+
+    #[test]
+    fn declared_type() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let uuid = column_typed("uuid", "UUID", Affinity::TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![uuid.clone()],
+        };
+
+        let sql = table.create_sql();
+        assert_eq!(sql, "CREATE TABLE my_table (uuid UUID NOT NULL)");
+        assert_eq!(
+            Some(&Declared("UUID".into(), Affinity::TEXT)),
+            uuid.data_type()
+        );
+        assert_eq!(Affinity::TEXT, uuid.data_type().unwrap().affinity());
+        assert_eq!(Affinity::NUMERIC, Type::from_declared("UUID").affinity());
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        let declared: String = conn
+            .query_row(
+                "SELECT type FROM pragma_table_info('my_table')",
+                params![],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!("UUID", declared);
+    }
+
+    #[test]
+    fn create_sql_eq() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col1 = column("col1", TEXT, []);
+        let col2 = column("col2", TEXT, []);
+        let table = MyTable {
+            columns: vec![
+                col1.clone(),
+                col2.clone(),
+                primary_key([col1.clone()]),
+                unique([col1.clone(), col2.clone()]),
+            ],
+        };
+
+        let same = MyTable {
+            columns: vec![
+                col1.clone(),
+                col2.clone(),
+                primary_key([col1.clone()]),
+                unique([col1.clone(), col2.clone()]),
+            ],
+        };
+        assert!(table.create_sql_eq(&same));
+        assert_eq!(None, table.create_sql_diff(&same));
+
+        let constraint_order = MyTable {
+            columns: vec![
+                col1.clone(),
+                col2.clone(),
+                unique([col1.clone(), col2.clone()]),
+                primary_key([col1.clone()]),
+            ],
+        };
+        assert!(table.create_sql_eq(&constraint_order));
+
+        let column_order = MyTable {
+            columns: vec![
+                col2.clone(),
+                col1.clone(),
+                primary_key([col1.clone()]),
+                unique([col1.clone(), col2.clone()]),
+            ],
+        };
+        assert!(!table.create_sql_eq(&column_order));
+        assert_eq!(
+            Some("column 0: col1 TEXT != col2 TEXT".into()),
+            table.create_sql_diff(&column_order)
+        );
+
+        let missing_constraint = MyTable {
+            columns: vec![col1.clone(), col2, primary_key([col1])],
+        };
+        assert_eq!(
+            Some("constraint: UNIQUE (col1, col2) is missing".into()),
+            table.create_sql_diff(&missing_constraint)
+        );
+    }
+
+    #[test]
+    fn unknown_constraint_column() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col1 = column("col1", TEXT, []);
+        let col2 = column("col2", TEXT, []);
+        let other = column("other", TEXT, []);
+
+        let table = MyTable {
+            columns: vec![
+                col1.clone(),
+                col2.clone(),
+                primary_key([col1.clone()]),
+                unique([col1.clone(), col2.clone()]),
+            ],
+        };
+        assert_eq!(Ok(()), table.validate());
+
+        let table = MyTable {
+            columns: vec![
+                col1.clone(),
+                col2,
+                unique([col1.clone(), other.clone()]),
+                foreign_key(other.clone(), REFERENCES, "other_table", col1, []),
+            ],
+        };
+        assert_eq!(
+            Err(vec![
+                SchemaError::UnknownConstraintColumn("other".into()),
+                SchemaError::UnknownConstraintColumn("other".into()),
+            ]),
+            table.validate()
+        );
+        assert_eq!(
+            Err(SchemaError::UnknownConstraintColumn("other".into())),
+            table.try_create_sql()
+        );
+    }
+
+    #[test]
+    fn rust_from_sql_expr() {
+        assert_eq!(
+            r#"row.get::<_, i64>("flag")? != 0"#,
+            BOOLEAN.rust_from_sql_expr("flag")
+        );
+        assert_eq!(
+            r#"row.get::<_, String>("name")?"#,
+            TEXT.rust_from_sql_expr("name")
+        );
+        assert_eq!(
+            r#"row.get::<_, i64>("id")?"#,
+            INTEGER.rust_from_sql_expr("id")
+        );
+        assert_eq!(
+            r#"row.get::<_, Vec<u8>>("data")?"#,
+            BLOB.rust_from_sql_expr("data")
+        );
+        assert_eq!(
+            r#"row.get::<_, String>("created_at")?"#,
+            DATETIME.rust_from_sql_expr("created_at")
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let flag: bool = conn
+            .query_row("SELECT 1 AS flag", params![], |row| {
+                Ok(row.get::<_, i64>("flag")? != 0)
+            })
+            .unwrap();
+        assert!(flag);
+    }
+
+    #[test]
+    fn autoincrement_with_composite_key() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let id = column("id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT]);
+        let col1 = column("col1", TEXT, []);
+        let table = MyTable {
+            columns: vec![id.clone(), col1.clone(), primary_key([id, col1.clone()])],
+        };
+        assert_eq!(
+            Err(SchemaError::AutoincrementWithCompositeKey("id".into())),
+            table.try_create_sql()
+        );
+        assert!(rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&table.create_sql(), params![])
+            .is_err());
+
+        let table = MyTable {
+            columns: vec![column("id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT]), col1],
+        };
+        let sql = table.try_create_sql().unwrap();
+        assert_eq!(
+            sql,
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY AUTOINCREMENT, col1 TEXT)"
+        );
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&sql, params![])
+            .unwrap();
+    }
+
+    #[test]
+    fn keyword_collisions() {
+        struct MyTable {
+            name: &'static str,
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                self.name
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let order = column("order", INTEGER, []);
+        let table = MyTable {
+            name: "my_table",
+            columns: vec![column("position", INTEGER, []), order.clone()],
+        };
+        assert_eq!(vec!["order".to_owned()], table.keyword_collisions());
+
+        let table = MyTable {
+            name: "Group",
+            columns: vec![order],
+        };
+        assert_eq!(
+            vec!["Group".to_owned(), "order".to_owned()],
+            table.keyword_collisions()
+        );
+    }
+
+    #[test]
+    fn write_create_sql() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col1 = column("col1", TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]),
+                col1.clone(),
+                unique([col1]),
+            ],
+        };
+
+        let mut fmt_buf = String::new();
+        table.write_create_sql(&mut fmt_buf).unwrap();
+        assert_eq!(table.create_sql(), fmt_buf);
+
+        let mut io_buf = Vec::<u8>::new();
+        table.write_create_sql_io(&mut io_buf).unwrap();
+        assert_eq!(table.create_sql().as_bytes(), io_buf.as_slice());
+    }
+
+    #[test]
+    fn foreignkey_action() {
+        struct MyTable {
+            name: &'static str,
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                self.name
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let parent_id = column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]);
+        let parent = MyTable {
+            name: "parent_table",
+            columns: vec![parent_id.clone()],
+        };
+
+        let child_id = column("parent_id", INTEGER, []);
+        let child = MyTable {
+            name: "child_table",
+            columns: vec![
+                child_id.clone(),
+                foreign_key(
+                    child_id.clone(),
+                    REFERENCES,
+                    &parent,
+                    parent_id.clone(),
+                    [
+                        ACTION(Trigger::OnDelete, ReferentialAction::SetNull),
+                        ACTION(Trigger::OnUpdate, ReferentialAction::Cascade),
+                    ],
+                ),
+            ],
+        };
+
+        let sql = child.create_sql();
+        assert_eq!(
+            sql,
+            "CREATE TABLE child_table (parent_id INTEGER, FOREIGN KEY (parent_id) REFERENCES parent_table (id) ON DELETE SET NULL ON UPDATE CASCADE)"
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute("PRAGMA foreign_keys = ON", params![]).unwrap();
+        conn.execute(&parent.create_sql(), params![]).unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute("INSERT INTO parent_table (id) VALUES (1)", params![])
+            .unwrap();
+        conn.execute("INSERT INTO child_table (parent_id) VALUES (1)", params![])
+            .unwrap();
+        conn.execute("DELETE FROM parent_table", params![]).unwrap();
+        let parent_id: Option<i64> = conn
+            .query_row("SELECT parent_id FROM child_table", params![], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(None, parent_id);
+
+        #[allow(deprecated)]
+        let deprecated = [ON_DELETE, SET_NULL]
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(
+            ACTION(Trigger::OnDelete, ReferentialAction::SetNull).to_string(),
+            deprecated
+        );
+    }
+
+    #[test]
+    fn table_def_cached_create_sql() {
+        let mut table = TableDef::new(
+            "my_table",
+            [column("id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT])],
+        );
+
+        let first = table.cached_create_sql();
+        let second = table.cached_create_sql();
+        assert!(core::ptr::eq(first, second));
+        assert_eq!(table.create_sql(), first);
+
+        table.push_column(column("val", TEXT, [NOT_NULL]));
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY AUTOINCREMENT, val TEXT NOT NULL)",
+            table.cached_create_sql()
+        );
+        assert_eq!(table.create_sql(), table.cached_create_sql());
+    }
+
+    #[test]
+    fn uuid_column_strict() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                uuid_column("id", true, [PRIMARY_KEY]),
+                uuid_column("other", false, []),
+            ],
+        };
+        assert_eq!(
+            "CREATE TABLE my_table (id TEXT PRIMARY KEY CHECK (length(id) = 36), other TEXT)",
+            table.create_sql()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(
+            "INSERT INTO my_table (id, other) VALUES (?, ?)",
+            params!["67e55044-10b1-426f-9247-bb680e5fe0c8", "short"],
+        )
+        .unwrap();
+        assert!(conn
+            .execute("INSERT INTO my_table (id) VALUES (?)", params!["67e55044"])
+            .is_err());
+    }
+
+    #[test]
+    fn default_numeric() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("negative", INTEGER, [DEFAULT(DefaultValue::Integer(-1))]),
+                column("zero", INTEGER, [DEFAULT(0.into())]),
+                column("fraction", REAL, [DEFAULT(DefaultValue::Real(-0.25))]),
+                column("scientific", REAL, [DEFAULT(1.5e3.into())]),
+            ],
+        };
+        let sql = table.create_sql();
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, negative INTEGER DEFAULT -1, zero INTEGER DEFAULT 0, fraction REAL DEFAULT -0.25, scientific REAL DEFAULT 1500)",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute("INSERT INTO my_table (id) VALUES (1)", params![])
+            .unwrap();
+        let row: (i64, i64, f64, f64) = conn
+            .query_row(
+                "SELECT negative, zero, fraction, scientific FROM my_table",
+                params![],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!((-1, 0, -0.25, 1500.0), row);
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("positive", REAL, [DEFAULT(f64::INFINITY.into())]),
+                column("negative", REAL, [DEFAULT(f64::NEG_INFINITY.into())]),
+            ],
+        };
+        let sql = table.create_sql();
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, positive REAL DEFAULT 9e999, negative REAL DEFAULT -9e999)",
+            sql
+        );
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute("INSERT INTO my_table (id) VALUES (1)", params![])
+            .unwrap();
+        let row: (String, f64, f64) = conn
+            .query_row(
+                "SELECT typeof(positive), positive, negative FROM my_table",
+                params![],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(("real".to_owned(), f64::INFINITY, f64::NEG_INFINITY), row);
+
+        let table = MyTable {
+            columns: vec![column("ratio", REAL, [DEFAULT(f64::NAN.into())])],
+        };
+        assert_eq!(
+            Err(SchemaError::InvalidDefaultValue("ratio".into())),
+            table.try_create_sql()
+        );
+    }
+
+    #[test]
+    fn rename_columns() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col1 = column("col1", TEXT, []);
+        let col2 = column("col2", TEXT, []);
+        let table = MyTable {
+            columns: vec![col1.clone(), col2.clone()],
+        };
+
+        let sql = rename_columns_sql(&table, &[(&col2, "second"), (&col1, "first")]).unwrap();
+        assert_eq!(
+            vec![
+                "ALTER TABLE my_table RENAME COLUMN col2 TO second",
+                "ALTER TABLE my_table RENAME COLUMN col1 TO first",
+            ],
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        for data in &sql {
+            conn.execute(data, params![]).unwrap();
+        }
+        conn.execute(
+            "INSERT INTO my_table (first, second) VALUES ('a', 'b')",
+            params![],
+        )
+        .unwrap();
+
+        assert_eq!(
+            Err(SchemaError::DuplicateRename("Same".into())),
+            rename_columns_sql(&table, &[(&col1, "same"), (&col2, "Same")])
+        );
+    }
+
+    #[test]
+    fn unique_constraint_to_index() {
+        struct MyTable {
+            name: &'static str,
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                self.name
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col1 = column("col1", TEXT, []);
+        let col2 = column("col2", TEXT, []);
+        let constraint = unique([col1.clone(), col2.clone()]);
+        let inline = MyTable {
+            name: "inline_table",
+            columns: vec![col1.clone(), col2.clone(), constraint.clone()],
+        };
+        let indexed = MyTable {
+            name: "indexed_table",
+            columns: vec![col1.clone(), col2.clone()],
+        };
+
+        let index = unique_index_sql(&indexed, "indexed_table_unique", &constraint).unwrap();
+        assert_eq!(
+            "CREATE UNIQUE INDEX indexed_table_unique ON indexed_table (col1, col2)",
+            index
+        );
+        assert_eq!(None, unique_index_sql(&indexed, "name", &col1));
+        assert_eq!(
+            None,
+            unique_index_sql(&indexed, "name", &primary_key([col1.clone()]))
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&inline.create_sql(), params![]).unwrap();
+        conn.execute(&indexed.create_sql(), params![]).unwrap();
+        conn.execute(&index, params![]).unwrap();
+        for data in ["inline_table", "indexed_table"] {
+            let insert = format!("INSERT INTO {} (col1, col2) VALUES (?, ?)", data);
+            conn.execute(&insert, params!["a", "b"]).unwrap();
+            conn.execute(&insert, params!["a", "c"]).unwrap();
+            assert!(conn.execute(&insert, params!["a", "b"]).is_err());
+        }
+    }
+
+    #[test]
+    fn create_sql_for_dialect() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        struct MySql;
+
+        impl Dialect for MySql {
+            fn autoincrement(&self) -> &str {
+                "AUTO_INCREMENT"
+            }
+        }
+
+        struct Postgres;
+
+        impl Dialect for Postgres {
+            fn autoincrement(&self) -> &str {
+                "GENERATED BY DEFAULT AS IDENTITY"
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT]).with_comment("key"),
+                column("val", TEXT, [NOT_NULL]),
+            ],
+        };
+
+        assert_eq!(table.create_sql(), table.create_sql_for(&Sqlite));
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY AUTOINCREMENT, val TEXT NOT NULL)",
+            table.create_sql_for(&Sqlite)
+        );
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY AUTO_INCREMENT, val TEXT NOT NULL)",
+            table.create_sql_for(&MySql)
+        );
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY GENERATED BY DEFAULT AS IDENTITY, val TEXT NOT NULL)",
+            table.create_sql_for(&Postgres)
+        );
+    }
+
+    #[test]
+    fn has_primary_key() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let options = ValidationOptions {
+            require_primary_key: true,
+            ..Default::default()
+        };
+
+        let inline = MyTable {
+            columns: vec![column("id", INTEGER, [PRIMARY_KEY]).with_comment("key")],
+        };
+        assert!(inline.has_primary_key());
+        assert_eq!(Ok(()), inline.validate_with(&options));
+
+        let col1 = column("col1", TEXT, []);
+        let col2 = column("col2", TEXT, []);
+        let composite = MyTable {
+            columns: vec![col1.clone(), col2.clone(), primary_key([col1, col2])],
+        };
+        assert!(composite.has_primary_key());
+        assert_eq!(Ok(()), composite.validate_with(&options));
+
+        let keyless = MyTable {
+            columns: vec![column("val", TEXT, [UNIQUE])],
+        };
+        assert!(!keyless.has_primary_key());
+        assert_eq!(Ok(()), keyless.validate());
+        assert_eq!(
+            Err(vec![SchemaError::NoPrimaryKey]),
+            keyless.validate_with(&options)
+        );
+    }
+
+    #[test]
+    fn insert_select() {
+        struct MyTable {
+            name: &'static str,
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                self.name
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let source = MyTable {
+            name: "source_table",
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("val", TEXT, []),
+            ],
+        };
+        let target_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let target_val = column("value", TEXT, []);
+        let target = MyTable {
+            name: "target_table",
+            columns: vec![target_id.clone(), target_val.clone()],
+        };
+
+        let sql = insert_select_sql(
+            &target,
+            &[target_id, target_val],
+            "id, val FROM source_table WHERE id > 1",
+        );
+        assert_eq!(
+            "INSERT INTO target_table (id, value) SELECT id, val FROM source_table WHERE id > 1",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&source.create_sql(), params![]).unwrap();
+        conn.execute(&target.create_sql(), params![]).unwrap();
+        conn.execute(
+            "INSERT INTO source_table (id, val) VALUES (1, 'a'), (2, 'b'), (3, 'c')",
+            params![],
+        )
+        .unwrap();
+        assert_eq!(2, conn.execute(&sql, params![]).unwrap());
+        let values = conn
+            .prepare("SELECT value FROM target_table ORDER BY id")
+            .unwrap()
+            .query_map(params![], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec!["b", "c"], values);
+    }
+
+    #[test]
+    fn rebuild_table() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let old = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("val", INTEGER, [NOT_NULL]),
+            ],
+        };
+        let new = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("value", REAL, [NOT_NULL]),
+            ],
+        };
+
+        let sql = rebuild_table_sql(&old, &new, &[("id", "id"), ("val", "value")]);
+        assert_eq!(
+            vec![
+                "CREATE TABLE my_table_rebuild (id INTEGER PRIMARY KEY, value REAL NOT NULL)",
+                "INSERT INTO my_table_rebuild (id, value) SELECT id, val FROM my_table",
+                "DROP TABLE my_table",
+                "ALTER TABLE my_table_rebuild RENAME TO my_table",
+            ],
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&old.create_sql(), params![]).unwrap();
+        conn.execute(
+            "INSERT INTO my_table (id, val) VALUES (1, 10), (2, 20)",
+            params![],
+        )
+        .unwrap();
+        for data in &sql {
+            conn.execute(data, params![]).unwrap();
+        }
+        conn.execute(
+            "INSERT INTO my_table (id, value) VALUES (3, 0.5)",
+            params![],
+        )
+        .unwrap();
+        let values = conn
+            .prepare("SELECT value FROM my_table ORDER BY id")
+            .unwrap()
+            .query_map(params![], |row| row.get::<_, f64>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![10.0, 20.0, 0.5], values);
+    }
+
+    #[test]
+    fn type_parameters() {
+        assert_eq!(Some(255), VARCHAR(255).length());
+        assert_eq!(Some(2), CHAR(2).length());
+        assert_eq!(None, DECIMAL(10, 2).length());
+        assert_eq!(None, TEXT.length());
+
+        assert_eq!(Some((10, 2)), DECIMAL(10, 2).precision_scale());
+        assert_eq!(None, NUMERIC.precision_scale());
+        assert_eq!(None, VARCHAR(255).precision_scale());
+
+        assert_eq!(Affinity::TEXT, VARCHAR(255).affinity());
+        assert_eq!(Affinity::TEXT, CHAR(2).affinity());
+        assert_eq!(Affinity::NUMERIC, DECIMAL(10, 2).affinity());
+
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("name", VARCHAR(255), []),
+                column("code", CHAR(2), []),
+                column("price", DECIMAL(10, 2), []),
+            ],
+        };
+        assert_eq!(
+            "CREATE TABLE my_table (name VARCHAR(255), code CHAR(2), price DECIMAL(10, 2))",
+            table.create_sql()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+    }
+
+    #[test]
+    fn normalized_create_sql() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("val", TEXT, [DEFAULT("a  b".into())]),
+            ],
+        };
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, val TEXT DEFAULT 'a  b')",
+            table.normalized_create_sql()
+        );
+        assert_eq!(
+            table.normalized_create_sql(),
+            normalize_sql(
+                "create table my_table (\n  id integer primary key ,\n  val TEXT default 'a  b'\n)"
+            )
+        );
+
+        #[cfg(feature = "rusqlite")]
+        {
+            let conn = rusqlite::Connection::open_in_memory().unwrap();
+            assert!(!table.matches_database(&conn));
+            conn.execute(
+                "create table my_table (\n  id integer primary key,\n  val TEXT default 'a  b'\n)",
+                params![],
+            )
+            .unwrap();
+            assert!(table.matches_database(&conn));
+            conn.execute("DROP TABLE my_table", params![]).unwrap();
+            conn.execute(
+                "CREATE TABLE my_table (id INTEGER PRIMARY KEY, val TEXT DEFAULT 'a b')",
+                params![],
+            )
+            .unwrap();
+            assert!(!table.matches_database(&conn));
+
+            let table = TableBuilder::new("my_table")
+                .column(column("id", INTEGER, [PRIMARY_KEY]))
+                .if_not_exists()
+                .build();
+            assert_eq!(
+                "CREATE TABLE my_table (id INTEGER PRIMARY KEY)",
+                table.normalized_create_sql()
+            );
+            let conn = rusqlite::Connection::open_in_memory().unwrap();
+            conn.execute(&table.create_sql(), params![]).unwrap();
+            assert!(table.matches_database(&conn));
+        }
+    }
+
+    #[test]
+    fn unique_expression_index() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("email", TEXT, [NOT_NULL]),
+            ],
+        };
+
+        let index = unique_expr_index("email_index", &table, &["lower(trim(email))"]);
+        assert_eq!(
+            "CREATE UNIQUE INDEX email_index ON my_table (lower(trim(email)))",
+            index
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(&index, params![]).unwrap();
+        let insert = "INSERT INTO my_table (email) VALUES (?)";
+        conn.execute(insert, params!["a@example.com"]).unwrap();
+        conn.execute(insert, params!["b@example.com"]).unwrap();
+        assert!(conn.execute(insert, params![" A@Example.com"]).is_err());
+    }
+
+    #[test]
+    fn references_deferred() {
+        struct MyTable {
+            name: &'static str,
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                self.name
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let author_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let book_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let author = MyTable {
+            name: "author",
+            columns: vec![
+                author_id.clone(),
+                column(
+                    "first_book_id",
+                    INTEGER,
+                    [
+                        NOT_NULL,
+                        references("book", book_id.clone(), [DEFERRABLE_INITIALLY_DEFERRED]),
+                    ],
+                ),
+            ],
+        };
+        let book = MyTable {
+            name: "book",
+            columns: vec![
+                book_id,
+                column(
+                    "author_id",
+                    INTEGER,
+                    [
+                        NOT_NULL,
+                        references(&author, author_id, [DEFERRABLE_INITIALLY_DEFERRED]),
+                    ],
+                ),
+            ],
+        };
+
+        assert_eq!(
+            "CREATE TABLE author (id INTEGER PRIMARY KEY, first_book_id INTEGER NOT NULL REFERENCES book (id) DEFERRABLE INITIALLY DEFERRED)",
+            author.create_sql()
+        );
+        assert_eq!(
+            Ok(INLINE_REFERENCES(
+                "book (id) DEFERRABLE INITIALLY DEFERRED".into()
+            )),
+            "references book (id) DEFERRABLE INITIALLY DEFERRED".parse()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute("PRAGMA foreign_keys = ON", params![]).unwrap();
+        conn.execute(&author.create_sql(), params![]).unwrap();
+        conn.execute(&book.create_sql(), params![]).unwrap();
+        conn.execute_batch(
+            "BEGIN;
+            INSERT INTO author (id, first_book_id) VALUES (1, 10);
+            INSERT INTO book (id, author_id) VALUES (10, 1);
+            COMMIT;",
+        )
+        .unwrap();
+
+        conn.execute_batch("BEGIN; INSERT INTO author (id, first_book_id) VALUES (2, 20);")
+            .unwrap();
+        assert!(conn.execute_batch("COMMIT").is_err());
+    }
+
+    #[test]
+    fn strict_generated_column() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+
+            fn strict(&self) -> bool {
+                true
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("a", INTEGER, [NOT_NULL]),
+                column("b", INTEGER, [NOT_NULL]),
+                column(
+                    "total",
+                    INT,
+                    [GENERATED("a + b".into(), GeneratedStorage::VIRTUAL)],
+                ),
+                column(
+                    "label",
+                    TEXT,
+                    [GENERATED("'#' || a".into(), GeneratedStorage::STORED)],
+                ),
+            ],
+        };
+        let sql = table.try_create_sql().unwrap();
+        assert_eq!(
+            "CREATE TABLE my_table (a INTEGER NOT NULL, b INTEGER NOT NULL, total INT GENERATED ALWAYS AS (a + b) VIRTUAL, label TEXT GENERATED ALWAYS AS ('#' || a) STORED) STRICT",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute("INSERT INTO my_table (a, b) VALUES (1, 2)", params![])
+            .unwrap();
+        let row: (i64, String) = conn
+            .query_row("SELECT total, label FROM my_table", params![], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((3, "#1".to_owned()), row);
+
+        let table = MyTable {
+            columns: vec![
+                column("a", INTEGER, [NOT_NULL]),
+                column(
+                    "created",
+                    DATETIME,
+                    [GENERATED(
+                        "datetime(a, 'unixepoch')".into(),
+                        GeneratedStorage::VIRTUAL,
+                    )],
+                ),
+            ],
+        };
+        assert_eq!(
+            Err(SchemaError::InvalidStrictType("created".into())),
+            table.try_create_sql()
+        );
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        assert!(conn.execute(&table.create_sql(), params![]).is_err());
+    }
+
+    #[test]
+    fn pragma_checks() {
+        assert_eq!("PRAGMA integrity_check", integrity_check_sql());
+        assert_eq!("PRAGMA foreign_key_check", foreign_key_check_sql(None));
+        assert_eq!(
+            "PRAGMA foreign_key_check(\"child_table\")",
+            foreign_key_check_sql(Some("child_table"))
+        );
+        assert_eq!(
+            "PRAGMA foreign_key_check(\"a\"\"b\")",
+            foreign_key_check_sql(Some("a\"b"))
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE parent_table (id INTEGER PRIMARY KEY);
+            CREATE TABLE child_table (parent_id INTEGER REFERENCES parent_table (id));
+            PRAGMA foreign_keys = OFF;
+            INSERT INTO child_table (parent_id) VALUES (1);",
+        )
+        .unwrap();
+        let problems = conn
+            .prepare(&foreign_key_check_sql(Some("child_table")))
+            .unwrap()
+            .query_map(params![], |row| row.get::<_, String>(2))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec!["parent_table"], problems);
+
+        #[cfg(feature = "rusqlite")]
+        {
+            assert_eq!(Ok(vec![]), integrity_check(&conn));
+            assert_eq!(
+                Ok(vec![ForeignKeyViolation {
+                    table: "child_table".into(),
+                    rowid: Some(1),
+                    parent: "parent_table".into(),
+                    fkid: 0,
+                }]),
+                foreign_key_check(&conn, None)
+            );
+            assert_eq!(Ok(vec![]), foreign_key_check(&conn, Some("parent_table")));
+        }
+
+        conn.execute_batch(
+            "CREATE TABLE \"odd\"\"child) \" (parent_id INTEGER REFERENCES parent_table (id));
+            INSERT INTO \"odd\"\"child) \" (parent_id) VALUES (2);",
+        )
+        .unwrap();
+        let problems = conn
+            .prepare(&foreign_key_check_sql(Some("odd\"child) ")))
+            .unwrap()
+            .query_map(params![], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec!["odd\"child) "], problems);
+    }
+
+    #[test]
+    fn default_references_column() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let options = ValidationOptions {
+            check_default_references: true,
+            ..Default::default()
+        };
+
+        let table = MyTable {
+            columns: vec![
+                column("other_col", INTEGER, []),
+                column(
+                    "next",
+                    INTEGER,
+                    [DEFAULT(DefaultValue::RawLiteral("(other_col + 1)".into()))],
+                ),
+            ],
+        };
+        assert!(table.try_create_sql().is_ok());
+        assert_eq!(
+            Err(SchemaError::DefaultReferencesColumn("next".into())),
+            table.try_create_sql_with(&options)
+        );
+
+        let table = MyTable {
+            columns: vec![
+                column("other_col", INTEGER, []),
+                column(
+                    "next",
+                    INTEGER,
+                    [DEFAULT(DefaultValue::RawLiteral("(1 + 1)".into()))],
+                ),
+                column(
+                    "label",
+                    TEXT,
+                    [DEFAULT(DefaultValue::RawLiteral(
+                        "('other_col' || 'x')".into(),
+                    ))],
+                ),
+            ],
+        };
+        let sql = table.try_create_sql_with(&options).unwrap();
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+    }
+
+    #[test]
+    fn type_ord() {
+        let mut types = vec![
+            Declared("UUID".into(), Affinity::TEXT),
+            Raw("VARCHAR".into()),
+            DECIMAL(10, 2),
+            VARCHAR(255),
+            TEXT,
+            DECIMAL(5, 2),
+            VARCHAR(16),
+            INTEGER,
+            ANY,
+            CHAR(2),
+            Raw("GEOMETRY".into()),
+            Declared("UUID".into(), Affinity::BLOB),
+        ];
+        types.sort();
+        assert_eq!(
+            vec![
+                INTEGER,
+                TEXT,
+                VARCHAR(16),
+                VARCHAR(255),
+                CHAR(2),
+                DECIMAL(5, 2),
+                DECIMAL(10, 2),
+                ANY,
+                Raw("GEOMETRY".into()),
+                Raw("VARCHAR".into()),
+                Declared("UUID".into(), Affinity::TEXT),
+                Declared("UUID".into(), Affinity::BLOB),
+            ],
+            types
+        );
+    }
+
+    #[test]
+    fn table_validated() {
+        let valid = table(
+            "my_table",
+            vec![
+                column("id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT]),
+                column("val", TEXT, [NOT_NULL]),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY AUTOINCREMENT, val TEXT NOT NULL)",
+            valid.cached_create_sql()
+        );
+
+        assert_eq!(
+            Some(SchemaError::DuplicateColumn("val".into())),
+            table(
+                "my_table",
+                vec![column("val", TEXT, []), column("val", INTEGER, [])],
+            )
+            .err()
+        );
+    }
+
+    #[test]
+    fn invalid_generated_column() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("val", TEXT, [NOT_NULL]),
+                column(
+                    "upper_val",
+                    TEXT,
+                    [
+                        GENERATED("upper(val)".into(), GeneratedStorage::STORED),
+                        NOT_NULL,
+                    ],
+                ),
+            ],
+        };
+        let sql = table.try_create_sql().unwrap();
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+
+        let table = MyTable {
+            columns: vec![
+                column("val", TEXT, [NOT_NULL]),
+                column(
+                    "upper_val",
+                    TEXT,
+                    [
+                        GENERATED("upper(val)".into(), GeneratedStorage::VIRTUAL),
+                        DEFAULT("a".into()),
+                    ],
+                ),
+            ],
+        };
+        assert_eq!(
+            Err(SchemaError::InvalidGeneratedColumn("upper_val".into())),
+            table.try_create_sql()
+        );
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        assert!(conn.execute(&table.create_sql(), params![]).is_err());
+    }
+
+    #[test]
+    fn table_name_alias() {
+        assert_eq!("users", TableName::from("users").to_string());
+        assert_eq!(
+            "users AS u",
+            TableName::from("users").alias("u").to_string()
+        );
+
+        let id = column("id", INTEGER, [PRIMARY_KEY]);
+        let user_id = column("user_id", INTEGER, []);
+        assert_eq!(
+            "FOREIGN KEY (user_id) REFERENCES users (id)",
+            foreign_key(
+                user_id,
+                REFERENCES,
+                TableName::from("users").alias("u"),
+                id.clone(),
+                []
+            )
+            .create_statement()
+        );
+        assert_eq!(
+            INLINE_REFERENCES("users (id)".into()),
+            references(TableName::from("users").alias("u"), id, [])
+        );
+    }
+
+    #[test]
+    fn select_join() {
+        struct MyTable {
+            name: &'static str,
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                self.name
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let users = MyTable {
+            name: "users",
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("name", TEXT, [NOT_NULL]),
+            ],
+        };
+        let title = column("title", TEXT, [NOT_NULL]);
+        let posts = MyTable {
+            name: "posts",
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("user_id", INTEGER, [NOT_NULL]),
+                title.clone(),
+            ],
+        };
+
+        let sql = select(TableName::from(&posts).alias("p"))
+            .term("u.name")
+            .term(title)
+            .join(
+                JoinKind::Inner,
+                TableName::from(&users).alias("u"),
+                "u.id = p.user_id",
+            )
+            .where_expr("p.id > 1")
+            .sql();
+        assert_eq!(
+            "SELECT u.name, title FROM posts AS p INNER JOIN users AS u ON u.id = p.user_id WHERE p.id > 1",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&users.create_sql(), params![]).unwrap();
+        conn.execute(&posts.create_sql(), params![]).unwrap();
+        conn.execute_batch(
+            "INSERT INTO users (id, name) VALUES (1, 'alice'), (2, 'bob');
+            INSERT INTO posts (id, user_id, title) VALUES (1, 1, 'a'), (2, 2, 'b'), (3, 1, 'c');",
+        )
+        .unwrap();
+        let rows = conn
+            .prepare(&sql)
+            .unwrap()
+            .query_map(params![], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                ("bob".to_owned(), "b".to_owned()),
+                ("alice".into(), "c".into())
+            ],
+            rows
+        );
+
+        assert_eq!(
+            "SELECT * FROM users LEFT JOIN posts ON posts.user_id = users.id",
+            select(&users)
+                .join(JoinKind::Left, &posts, "posts.user_id = users.id")
+                .sql()
+        );
+        assert_eq!(
+            "SELECT * FROM users CROSS JOIN posts",
+            select(&users).join(JoinKind::Cross, &posts, "").sql()
+        );
+    }
+
+    #[test]
+    fn select_order_by_limit_offset() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let id = column("id", INTEGER, [PRIMARY_KEY]);
+        let category = column("category", TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![id.clone(), category.clone()],
+        };
+
+        assert_eq!(
+            "SELECT * FROM my_table ORDER BY category ASC, id DESC",
+            select(&table)
+                .order_by(&[
+                    (category.clone(), SortOrder::ASC),
+                    (id.clone(), SortOrder::DESC)
+                ])
+                .sql()
+        );
+        assert_eq!(
+            "SELECT * FROM my_table LIMIT 10",
+            select(&table).limit(10).sql()
+        );
+        assert_eq!(
+            "SELECT * FROM my_table LIMIT -1 OFFSET 20",
+            select(&table).offset(20).sql()
+        );
+
+        let sql = select(&table)
+            .term(id.clone())
+            .where_expr("id > 1")
+            .offset(1)
+            .order_by(&[(category, SortOrder::ASC), (id, SortOrder::DESC)])
+            .limit(2)
+            .sql();
+        assert_eq!(
+            "SELECT id FROM my_table WHERE id > 1 ORDER BY category ASC, id DESC LIMIT 2 OFFSET 1",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(
+            "INSERT INTO my_table (id, category) VALUES (1, 'a'), (2, 'b'), (3, 'a'), (4, 'b'), (5, 'a')",
+            params![],
+        )
+        .unwrap();
+        let query = |sql: &str| {
+            conn.prepare(sql)
+                .unwrap()
+                .query_map(params![], |row| row.get::<_, i64>(0))
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+        assert_eq!(vec![3, 4], query(&sql));
+        assert_eq!(
+            vec![3, 4, 5],
+            query(&select(&table).term("id").offset(2).sql())
+        );
+    }
+
+    #[test]
+    fn select_group_by_having() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let category = column("category", TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![column("id", INTEGER, [PRIMARY_KEY]), category.clone()],
+        };
+
+        let sql = select(&table)
+            .term(category.clone())
+            .term(SelectTerm::Expr("COUNT(*)".into()))
+            .order_by(&[(category.clone(), SortOrder::ASC)])
+            .having("COUNT(*) > 1")
+            .group_by(&[category])
+            .where_expr("id < 10")
+            .sql();
+        assert_eq!(
+            "SELECT category, COUNT(*) FROM my_table WHERE id < 10 GROUP BY category HAVING COUNT(*) > 1 ORDER BY category ASC",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(
+            "INSERT INTO my_table (category) VALUES ('a'), ('b'), ('c'), ('a'), ('c'), ('c')",
+            params![],
+        )
+        .unwrap();
+        let rows = conn
+            .prepare(&sql)
+            .unwrap()
+            .query_map(params![], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![("a".to_owned(), 2), ("c".to_owned(), 3)], rows);
+    }
+
+    #[test]
+    fn select_distinct() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let category = column("category", TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![column("id", INTEGER, [PRIMARY_KEY]), category.clone()],
+        };
+
+        assert_eq!(
+            "SELECT DISTINCT * FROM my_table",
+            select(&table).distinct().sql()
+        );
+        let sql = select(&table)
+            .term(category.clone())
+            .distinct()
+            .where_expr("id > 1")
+            .order_by(&[(category, SortOrder::DESC)])
+            .limit(10)
+            .sql();
+        assert_eq!(
+            "SELECT DISTINCT category FROM my_table WHERE id > 1 ORDER BY category DESC LIMIT 10",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(
+            "INSERT INTO my_table (category) VALUES ('a'), ('b'), ('a'), ('b'), ('a')",
+            params![],
+        )
+        .unwrap();
+        let rows = conn
+            .prepare(&sql)
+            .unwrap()
+            .query_map(params![], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec!["b", "a"], rows);
+    }
+
+    #[test]
+    fn rowid_alias() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![rowid_alias_column("id"), column("val", TEXT, [])],
+        };
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, val TEXT)",
+            table.create_sql()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute("INSERT INTO my_table (val) VALUES ('a'), ('b')", params![])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO my_table (rowid, val) VALUES (10, 'c')",
+            params![],
+        )
+        .unwrap();
+        let rows = conn
+            .prepare("SELECT id, rowid FROM my_table ORDER BY id")
+            .unwrap()
+            .query_map(params![], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![(1, 1), (2, 2), (10, 10)], rows);
+    }
+
+    #[test]
+    fn default_control_characters() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let options = ValidationOptions {
+            reject_nul_defaults: true,
+            ..Default::default()
+        };
+
+        let table = MyTable {
+            columns: vec![column("val", TEXT, [DEFAULT("a\0b".into())])],
+        };
+        assert!(table.try_create_sql().is_ok());
+        assert_eq!(
+            Err(SchemaError::InvalidDefaultValue("val".into())),
+            table.try_create_sql_with(&options)
+        );
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("val", TEXT, [DEFAULT("it's\nfine".into())]),
+            ],
+        };
+        let sql = table.try_create_sql_with(&options).unwrap();
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, val TEXT DEFAULT 'it''s\nfine')",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute("INSERT INTO my_table (id) VALUES (1)", params![])
+            .unwrap();
+        let val: String = conn
+            .query_row("SELECT val FROM my_table", params![], |row| row.get(0))
+            .unwrap();
+        assert_eq!("it's\nfine", val);
+    }
+
+    #[cfg(feature = "ir")]
+    #[test]
+    fn table_def_to_ir() {
+        let id = column("id", INTEGER, [PRIMARY_KEY]);
+        let parent_id = column("parent_id", INTEGER, [NOT_NULL]);
+        let name = column("name", TEXT, [NOT_NULL, COLLATE(CollationName::NOCASE)]);
+        let table = TableDef::new(
+            "child_table",
+            [
+                id.clone(),
+                parent_id.clone(),
+                name.clone().with_comment("display name"),
+                foreign_key(
+                    parent_id.clone(),
+                    REFERENCES,
+                    "parent_table",
+                    id,
+                    [ACTION(Trigger::OnDelete, ReferentialAction::Cascade)],
+                ),
+                unique([parent_id, name]),
+            ],
+        );
+
+        assert_eq!(
+            SchemaIr {
+                name: "child_table".into(),
+                columns: vec![
+                    ColumnIr {
+                        name: "id".into(),
+                        declared_type: "INTEGER".into(),
+                        affinity: Affinity::INTEGER,
+                        attributes: vec!["PRIMARY KEY".into()],
+                    },
+                    ColumnIr {
+                        name: "parent_id".into(),
+                        declared_type: "INTEGER".into(),
+                        affinity: Affinity::INTEGER,
+                        attributes: vec!["NOT NULL".into()],
+                    },
+                    ColumnIr {
+                        name: "name".into(),
+                        declared_type: "TEXT".into(),
+                        affinity: Affinity::TEXT,
+                        attributes: vec!["NOT NULL".into(), "COLLATE NOCASE".into()],
+                    },
+                ],
+                constraints: vec![
+                    ConstraintIr {
+                        kind: ConstraintKind::ForeignKey,
+                        columns: vec!["parent_id".into()],
+                        references: Some(("parent_table".into(), vec!["id".into()])),
+                        sql:
+                            "FOREIGN KEY (parent_id) REFERENCES parent_table (id) ON DELETE CASCADE"
+                                .into(),
+                    },
+                    ConstraintIr {
+                        kind: ConstraintKind::Unique,
+                        columns: vec!["parent_id".into(), "name".into()],
+                        references: None,
+                        sql: "UNIQUE (parent_id, name)".into(),
+                    },
+                ],
+            },
+            table.to_ir()
+        );
+    }
+
+    #[test]
+    fn table_builder_constraints() {
+        let col1 = column("col1", TEXT, [NOT_NULL]);
+        let col2 = column("col2", INTEGER, [NOT_NULL]);
+        let col3 = column("col3", TEXT, []);
+        let table = TableBuilder::new("my_table")
+            .column(col1.clone())
+            .column(col2.clone())
+            .column(col3.clone())
+            .primary_key_of(&[col1.clone(), col2])
+            .unique_of(&[col3])
+            .check_expr("col2 > 0")
+            .index("my_table_index", [col1])
+            .build();
+
+        assert_eq!(
+            "CREATE TABLE my_table (col1 TEXT NOT NULL, col2 INTEGER NOT NULL, col3 TEXT, PRIMARY KEY (col1, col2), UNIQUE (col3), CHECK (col2 > 0))",
+            table.try_create_sql().unwrap()
+        );
+        assert_eq!(
+            vec!["CREATE INDEX my_table_index ON my_table (col1)"],
+            table.create_index()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for data in table.create_all_sql() {
+            conn.execute(&data, params![]).unwrap();
+        }
+        assert!(conn
+            .execute(
+                "INSERT INTO my_table (col1, col2) VALUES ('a', 0)",
+                params![]
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn table_def_from_sorted_map() {
+        let entries = || {
+            vec![
+                ("val".to_owned(), (TEXT, vec![NOT_NULL])),
+                ("id".to_owned(), (INTEGER, vec![PRIMARY_KEY])),
+                ("created".to_owned(), (DATETIME, vec![])),
+            ]
+        };
+        let a = TableDef::from_sorted_map("my_table", entries().into_iter().collect());
+        let b = TableDef::from_sorted_map("my_table", entries().into_iter().rev().collect());
+
+        assert_eq!(
+            "CREATE TABLE my_table (created DATETIME, id INTEGER PRIMARY KEY, val TEXT NOT NULL)",
+            a.create_sql()
+        );
+        assert_eq!(a.create_sql(), b.create_sql());
+    }
+
+    #[test]
+    fn create_sql_styled() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "MyTable"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("Id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT]),
+                column(
+                    "Status",
+                    TEXT,
+                    [
+                        NOT_NULL,
+                        DEFAULT("Active".into()),
+                        CHECK("Status IN ('Active', 'Inactive')".into()),
+                        COLLATE(CollationName::NOCASE),
+                    ],
+                ),
+                column("Price", DECIMAL(10, 2), []),
+            ],
+        };
+
+        assert_eq!(
+            table.create_sql(),
+            table.create_sql_styled(CaseStyle::Upper)
+        );
+        assert_eq!(
+            "CREATE TABLE MyTable (Id INTEGER PRIMARY KEY AUTOINCREMENT, Status TEXT NOT NULL DEFAULT 'Active' CHECK (Status IN ('Active', 'Inactive')) COLLATE NOCASE, Price DECIMAL(10, 2))",
+            table.create_sql_styled(CaseStyle::Upper)
+        );
+        let lower = table.create_sql_styled(CaseStyle::Lower);
+        assert_eq!(
+            "create table MyTable (Id integer primary key autoincrement, Status text not null default 'Active' check (Status IN ('Active', 'Inactive')) collate NOCASE, Price decimal(10, 2))",
+            lower
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&lower, params![]).unwrap();
+
+        let a = column("a", INTEGER, []);
+        let b = column("b", INTEGER, []);
+        let parent = MyTable {
+            columns: vec![
+                a.clone(),
+                b.clone(),
+                primary_key([a.clone(), b.clone()]),
+                unique([a.clone()]),
+            ],
+        };
+        let lower = parent.create_sql_styled(CaseStyle::Lower);
+        assert_eq!(
+            "create table MyTable (a integer, b integer, primary key (a, b), unique (a))",
+            lower
+        );
+        conn.execute("DROP TABLE MyTable", params![]).unwrap();
+        conn.execute(&lower, params![]).unwrap();
+
+        let child = TableDef::new(
+            "Child",
+            [
+                a.clone(),
+                foreign_key_with_actions(
+                    a.clone(),
+                    &parent,
+                    a,
+                    &[(Trigger::OnDelete, ReferentialAction::Cascade)],
+                ),
+            ],
+        );
+        let lower = child.create_sql_styled(CaseStyle::Lower);
+        assert_eq!(
+            "create table Child (a integer, foreign key (a) references MyTable (a) on delete cascade)",
+            lower
+        );
+        conn.execute(&lower, params![]).unwrap();
+    }
+
+    #[test]
+    fn enum_column_check() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![enum_column(
+                "status",
+                &["active", "inactive", "won't do"],
+                [NOT_NULL],
+            )],
+        };
+        assert_eq!(
+            "CREATE TABLE my_table (status TEXT NOT NULL CHECK (status IN ('active', 'inactive', 'won''t do')))",
+            table.create_sql()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        let insert = "INSERT INTO my_table (status) VALUES (?)";
+        conn.execute(insert, params!["active"]).unwrap();
+        conn.execute(insert, params!["won't do"]).unwrap();
+        assert!(conn.execute(insert, params!["deleted"]).is_err());
+    }
+
+    #[test]
+    fn binary_types() {
+        assert_eq!(Affinity::BLOB, BINARY(16).affinity());
+        assert_eq!(Affinity::BLOB, VARBINARY(255).affinity());
+        assert_eq!(Some(16), BINARY(16).length());
+        assert_eq!(Some(255), VARBINARY(255).length());
+        assert_eq!(
+            "row.get::<_, Vec<u8>>(\"hash\")?",
+            BINARY(16).rust_from_sql_expr("hash")
+        );
+
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("hash", BINARY(16), []),
+                column("payload", VARBINARY(255), []),
+            ],
+        };
+        assert_eq!(
+            "CREATE TABLE my_table (hash BINARY(16), payload VARBINARY(255))",
+            table.create_sql()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(
+            "INSERT INTO my_table (hash, payload) VALUES (?, ?)",
+            params![vec![0u8; 16], b"1".to_vec()],
+        )
+        .unwrap();
+        let types: (String, String) = conn
+            .query_row(
+                "SELECT typeof(hash), typeof(payload) FROM my_table",
+                params![],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(("blob".to_owned(), "blob".to_owned()), types);
+    }
+
+    #[cfg(feature = "rusqlite")]
+    #[test]
+    fn check_sql() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("name", VARCHAR(16), [NOT_NULL]),
+            ],
+        };
+        assert!(table.check_sql().is_ok());
+
+        let table = MyTable {
+            columns: vec![column("name", Raw("VARCHAR((16".into()), [])],
+        };
+        assert!(table.validate().is_ok());
+        assert!(table.check_sql().is_err());
+    }
+
+    #[test]
+    fn delete_children_first() {
+        struct MyTable {
+            name: &'static str,
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                self.name
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let parent_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let parent = MyTable {
+            name: "parent_table",
+            columns: vec![parent_id.clone()],
+        };
+        let child_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let child_parent_id = column("parent_id", INTEGER, [NOT_NULL]);
+        let child = MyTable {
+            name: "child_table",
+            columns: vec![
+                child_id.clone(),
+                child_parent_id.clone(),
+                foreign_key(child_parent_id, REFERENCES, &parent, parent_id, []),
+            ],
+        };
+        let grandchild = MyTable {
+            name: "grandchild_table",
+            columns: vec![column(
+                "child_id",
+                INTEGER,
+                [NOT_NULL, references(&child, child_id, [])],
+            )],
+        };
+
+        let sql = delete_children_first_sql(&parent, &[&child, &grandchild]);
+        assert_eq!(
+            vec![
+                "DELETE FROM grandchild_table",
+                "DELETE FROM child_table",
+                "DELETE FROM parent_table",
+            ],
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute("PRAGMA foreign_keys = ON", params![]).unwrap();
+        for data in [&parent, &child, &grandchild] {
+            conn.execute(&data.create_sql(), params![]).unwrap();
+        }
+        conn.execute_batch(
+            "INSERT INTO parent_table (id) VALUES (1);
+            INSERT INTO child_table (id, parent_id) VALUES (1, 1);
+            INSERT INTO grandchild_table (child_id) VALUES (1);",
+        )
+        .unwrap();
+        assert!(conn.execute("DELETE FROM parent_table", params![]).is_err());
+        for data in &sql {
+            conn.execute(data, params![]).unwrap();
+        }
+    }
+
+    #[test]
+    fn select_order_by_collate() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let id = column("id", INTEGER, [PRIMARY_KEY]);
+        let name = column("name", TEXT, [NOT_NULL]);
+        let table = MyTable {
+            columns: vec![id.clone(), name.clone()],
+        };
+
+        let sql = select(&table)
+            .term(name.clone())
+            .order_by_terms([
+                IndexTerm::new(name)
+                    .collate(CollationName::NOCASE)
+                    .order(SortOrder::ASC),
+                IndexTerm::new(id).order(SortOrder::DESC),
+            ])
+            .sql();
+        assert_eq!(
+            "SELECT name FROM my_table ORDER BY name COLLATE NOCASE ASC, id DESC",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(
+            "INSERT INTO my_table (name) VALUES ('b'), ('A'), ('a'), ('B')",
+            params![],
+        )
+        .unwrap();
+        let rows = conn
+            .prepare(&sql)
+            .unwrap()
+            .query_map(params![], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec!["a", "A", "B", "b"], rows);
+    }
+
+    #[test]
+    fn with_attribute() {
+        let val = column("val", TEXT, []);
+        let required = val.with_attribute(NOT_NULL);
+        assert_eq!("val TEXT", val.create_statement());
+        assert_eq!("val TEXT NOT NULL", required.create_statement());
+        assert_eq!(
+            "val TEXT NOT NULL DEFAULT 'a'",
+            required
+                .with_comment("value")
+                .with_attribute(DEFAULT("a".into()))
+                .create_statement()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "with_attribute called on table constraint UNIQUE (val)")]
+    fn with_attribute_constraint() {
+        unique([column("val", TEXT, [])]).with_attribute(NOT_NULL);
+    }
+
+    #[test]
+    fn fts5_unindexed() {
+        let sql = create_fts5_sql(
+            "documents",
+            &[Fts5Column::new("body"), Fts5Column::new("path").unindexed()],
+        );
+        assert_eq!(
+            "CREATE VIRTUAL TABLE documents USING fts5(body, path UNINDEXED)",
+            sql
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute(
+            "INSERT INTO documents (body, path) VALUES ('hello world', 'notes'), ('notes on rust', 'hello')",
+            params![],
+        )
+        .unwrap();
+        let query = |term: &str| {
+            conn.prepare("SELECT path FROM documents WHERE documents MATCH ?")
+                .unwrap()
+                .query_map(params![term], |row| row.get::<_, String>(0))
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+        assert_eq!(vec!["notes"], query("hello"));
+        assert_eq!(vec!["hello"], query("notes"));
+    }
+
+    #[test]
+    fn column_at() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let col1 = column("col1", TEXT, []);
+        let col2 = column("col2", TEXT, []).with_comment("second");
+        let table: Box<dyn Table> = Box::new(MyTable {
+            columns: vec![
+                col1.clone(),
+                unique([col1.clone()]),
+                col2.clone(),
+                check("col1 <> col2"),
+            ],
+        });
+
+        assert_eq!("col2", table.column_at(2).unwrap().name());
+        assert!(table.column_at(1).unwrap().is_constraint());
+        assert!(table.column_at(4).is_none());
+        assert_eq!(
+            vec!["col1", "col2"],
+            table
+                .data_columns()
+                .map(|data| data.name())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn default_null() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let table = MyTable {
+            columns: vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("val", TEXT, [DEFAULT(DefaultValue::Null)]),
+            ],
+        };
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, val TEXT DEFAULT NULL)",
+            table.create_sql()
+        );
+        assert_eq!(Ok(DEFAULT(DefaultValue::Null)), "DEFAULT null".parse());
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute("INSERT INTO my_table (id) VALUES (1)", params![])
+            .unwrap();
+        let val: Option<String> = conn
+            .query_row("SELECT val FROM my_table", params![], |row| row.get(0))
+            .unwrap();
+        assert_eq!(None, val);
+    }
+
+    #[test]
+    fn analyze_statement() {
+        assert_eq!("ANALYZE", analyze_sql(None));
+        assert_eq!("ANALYZE \"users\"", analyze_sql(Some("users")));
+        assert_eq!("ANALYZE \"a\"\"b\"", analyze_sql(Some("a\"b")));
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+            CREATE INDEX users_name ON users (name);
+            INSERT INTO users (name) VALUES ('a'), ('b'), ('c');",
+        )
+        .unwrap();
+        conn.execute_batch(&analyze_sql(Some("users"))).unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_stat1 WHERE tbl = 'users'",
+                params![],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(1, count);
+
+        #[cfg(feature = "rusqlite")]
+        {
+            analyze(&conn, Some("users_name")).unwrap();
+            analyze(&conn, None).unwrap();
+        }
+    }
+
+    #[test]
+    fn check_columns_template() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+        }
+
+        let start = column("start", INTEGER, [NOT_NULL]);
+        let end = column("end_at", INTEGER, [NOT_NULL]);
+        let constraint = check_columns(&[&start, &end], "{0} <= {1} AND {0} >= 0").unwrap();
+        assert_eq!(
+            "CHECK (\"start\" <= \"end_at\" AND \"start\" >= 0)",
+            constraint.create_statement()
+        );
+        assert_eq!(
+            "CHECK (\"order\" > 0)",
+            check_columns(&[&column("order", INTEGER, [])], "{0} > 0")
+                .unwrap()
+                .create_statement()
+        );
+
+        let table = MyTable {
+            columns: vec![start.clone(), end.clone(), constraint],
+        };
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(
+            "INSERT INTO my_table (start, end_at) VALUES (1, 2)",
+            params![],
+        )
+        .unwrap();
+        assert!(conn
+            .execute(
+                "INSERT INTO my_table (start, end_at) VALUES (2, 1)",
+                params![]
+            )
+            .is_err());
+
+        for data in ["{0} <= 1", "{0} <= {2}", "{0} <= {1", "{0} <= {x}"] {
+            assert_eq!(
+                Some(SchemaError::InvalidCheckTemplate(data.into())),
+                check_columns(&[&start, &end], data).err()
+            );
         }
     }
-}
 
-pub enum Column {
-    Column {
-        name: String,
-        column_type: Type,
-        attributes: Option<Vec<Attribute>>,
-    },
-    Constraint(String),
-}
+    #[test]
+    fn table_def_from_tuple() {
+        let table: TableDef = (
+            "users",
+            vec![
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("name", TEXT, [NOT_NULL]),
+            ],
+        )
+            .into();
+        assert_eq!(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+            table.create_sql()
+        );
+    }
 
-impl Column {
-    pub fn name(&self) -> &str {
-        match self {
-            Column::Column { name, .. } => name,
-            _ => panic!(),
-        }
+    #[test]
+    fn export_schema_script() {
+        let parent_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let name = column("name", TEXT, [NOT_NULL]);
+        let mut parent = TableDef::new("parent_table", [parent_id.clone(), name.clone()]);
+        parent.push_index("parent_name", [name]);
+
+        let child_parent_id = column("parent_id", INTEGER, [NOT_NULL]);
+        let child = TableDef::new(
+            "child_table",
+            [
+                child_parent_id.clone(),
+                foreign_key(child_parent_id.clone(), REFERENCES, &parent, parent_id, []),
+            ],
+        );
+
+        let script = export_schema(
+            &[&parent, &child],
+            &[IndexDef::new("child_parent", &child, [child_parent_id]).unique()],
+        );
+        assert_eq!(
+            "-- Generated by tinytable; do not edit.
+PRAGMA foreign_keys=OFF;
+CREATE TABLE parent_table (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+CREATE TABLE child_table (parent_id INTEGER NOT NULL, FOREIGN KEY (parent_id) REFERENCES parent_table (id));
+CREATE INDEX parent_name ON parent_table (name);
+CREATE UNIQUE INDEX child_parent ON child_table (parent_id);
+PRAGMA foreign_keys=ON;
+",
+            script
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(&script).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master", params![], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(4, count);
     }
 
-    fn create_statement(&self) -> String {
-        match self {
-            Column::Column {
-                name,
-                column_type,
-                attributes,
-            } => match attributes {
-                Some(attributes) => format!(
-                    "{} {} {}",
-                    name,
-                    column_type.name(),
-                    attributes
-                        .iter()
-                        .map(|data| data.name())
-                        .collect::<Vec<_>>()
-                        .join(" ")
-                ),
-                None => format!("{} {}", name, column_type.name()),
-            },
-            Column::Constraint(value) => value.into(),
-        }
+    #[test]
+    fn raw_column() {
+        let table = TableDef::new(
+            "my_table",
+            [
+                column("id", INTEGER, [PRIMARY_KEY]),
+                Arc::new(Column::Raw(
+                    "legacy_id INTEGER CHECK (legacy_id > 0)".into(),
+                )),
+                column("name", TEXT, [NOT_NULL]),
+            ],
+        );
+        assert_eq!("legacy_id", table.columns()[1].name());
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, legacy_id INTEGER CHECK (legacy_id > 0), name TEXT NOT NULL)",
+            table.create_sql()
+        );
+        assert!(table.validate().is_ok());
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        assert!(conn
+            .execute(
+                "INSERT INTO my_table (legacy_id, name) VALUES (0, 'a')",
+                params![]
+            )
+            .is_err());
     }
 
-    pub fn create_add_sql(&self) -> String {
-        match self {
-            Column::Column { name, .. } => {
-                format!("ALTER TABLE {} ADD {}", name, self.create_statement())
+    #[test]
+    fn autoincrement_errors() {
+        let id = column("id", INTEGER, [AUTOINCREMENT]);
+        let table = TableDef::new("my_table", [id.clone()]);
+        assert_eq!(
+            Err(vec![SchemaError::AutoincrementWithoutPrimaryKey(
+                "id".into()
+            )]),
+            table.validate()
+        );
+
+        let table = TableDef::new("my_table", [id.clone(), primary_key([id])]);
+        assert_eq!(
+            Err(vec![SchemaError::AutoincrementWithCompositeKey(
+                "id".into()
+            )]),
+            table.validate()
+        );
+
+        let table = TableDef::new(
+            "my_table",
+            [column("id", INTEGER, [PRIMARY_KEY, AUTOINCREMENT])],
+        );
+        assert_eq!(Ok(()), table.validate());
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&table.create_sql(), params![])
+            .unwrap();
+    }
+
+    #[test]
+    fn custom_table_options() {
+        struct MyTable {
+            columns: Vec<Arc<Column>>,
+        }
+
+        impl Table for MyTable {
+            fn name(&self) -> &str {
+                "my_table"
+            }
+
+            fn columns(&self) -> &[Arc<Column>] {
+                &self.columns
+            }
+
+            fn strict(&self) -> bool {
+                true
+            }
+
+            fn table_options(&self) -> Vec<String> {
+                vec!["WITHOUT ROWID".into()]
             }
-            _ => panic!(),
         }
+
+        let table = MyTable {
+            columns: vec![column("id", TEXT, [PRIMARY_KEY, NOT_NULL])],
+        };
+        let sql = table.create_sql();
+        assert_eq!(
+            "CREATE TABLE my_table (id TEXT PRIMARY KEY NOT NULL) STRICT, WITHOUT ROWID",
+            sql
+        );
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&sql, params![])
+            .unwrap();
     }
-}
 
-pub struct TableName(String);
+    #[test]
+    fn styled_raw_type() {
+        let table = TableDef::new(
+            "my_table",
+            [
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("val", Type::Raw("MyCustomType".into()), [NOT_NULL]),
+            ],
+        );
+        let sql = table.create_sql_styled(CaseStyle::Lower);
+        assert_eq!(
+            "create table my_table (id integer primary key, val MyCustomType not null)",
+            sql
+        );
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&sql, params![])
+            .unwrap();
 
-impl<T: Table> From<&T> for TableName {
-    fn from(value: &T) -> Self {
-        Self(value.name().into())
+        let table = TableDef::new(
+            "my_table",
+            [column_typed(
+                "x",
+                "MyCustomType",
+                Affinity::TEXT,
+                [NOT_NULL],
+            )],
+        );
+        assert_eq!(
+            "create table my_table (x MyCustomType not null)",
+            table.create_sql_styled(CaseStyle::Lower)
+        );
     }
-}
 
-impl From<String> for TableName {
-    fn from(value: String) -> Self {
-        Self(value)
-    }
-}
+    #[test]
+    fn referenced_tables_of_foreign_keys() {
+        let user_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let users = TableDef::new("users", [user_id.clone()]);
+        let group_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let groups = TableDef::new("groups", [group_id.clone()]);
 
-impl From<&str> for TableName {
-    fn from(value: &str) -> Self {
-        Self(value.into())
-    }
-}
+        let member_user = column("user_id", INTEGER, [NOT_NULL]);
+        let member_group = column("group_id", INTEGER, [NOT_NULL]);
+        let members = TableDef::new(
+            "members",
+            [
+                member_user.clone(),
+                member_group.clone(),
+                foreign_key(member_user, REFERENCES, &users, user_id, []),
+                foreign_key(member_group, REFERENCES, &groups, group_id, []),
+            ],
+        );
+        assert_eq!(vec!["users", "groups"], members.referenced_tables());
+        assert!(users.referenced_tables().is_empty());
 
-#[cfg(test)]
-mod tests {
-    use crate::Attribute::*;
-    use crate::ForeignKeyAttribute::*;
-    use crate::Type::*;
-    use crate::*;
-    use rusqlite::params;
-    use std::sync::Arc;
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for table in [&users as &dyn Table, &groups, &members] {
+            conn.execute(&table.create_sql(), params![]).unwrap();
+        }
+    }
 
     #[test]
-    fn empty_arr() {
+    fn structured_foreign_key() {
+        let parent_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let parent = TableDef::new("parent_table", [parent_id.clone()]);
+        let child_parent_id = column("parent_id", INTEGER, []);
+        let fk = foreign_key(
+            child_parent_id.clone(),
+            REFERENCES,
+            &parent,
+            parent_id,
+            [
+                ACTION(Trigger::OnDelete, ReferentialAction::Cascade),
+                DEFERRABLE_INITIALLY_DEFERRED,
+            ],
+        );
         assert_eq!(
-            (&[][..] as &[&str])
-                .iter()
-                .map(|data| data.to_owned())
-                .collect::<Vec<_>>()
-                .join(" "),
-            String::new()
-        )
+            "FOREIGN KEY (parent_id) REFERENCES parent_table (id) ON DELETE CASCADE DEFERRABLE INITIALLY DEFERRED",
+            fk.create_statement()
+        );
+        match &*fk {
+            Column::ForeignKey {
+                local,
+                table,
+                references,
+                actions,
+                deferred,
+            } => {
+                assert_eq!(&vec!["parent_id".to_owned()], local);
+                assert_eq!("parent_table", table);
+                assert_eq!(&vec!["id".to_owned()], references);
+                assert_eq!(
+                    &vec![(Trigger::OnDelete, ReferentialAction::Cascade)],
+                    actions
+                );
+                assert!(deferred);
+            }
+            _ => panic!(),
+        }
+
+        #[allow(deprecated)]
+        let deprecated = foreign_key(
+            child_parent_id.clone(),
+            REFERENCES,
+            &parent,
+            column("id", INTEGER, []),
+            [ON_DELETE, CASCADE, DEFERRABLE_INITIALLY_DEFERRED],
+        );
+        assert_eq!(fk.create_statement(), deprecated.create_statement());
+
+        let child = TableDef::new("child_table", [child_parent_id, fk]);
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&parent.create_sql(), params![]).unwrap();
+        conn.execute(&child.create_sql(), params![]).unwrap();
     }
 
     #[test]
-    fn all_types() {
-        let integer = column("integer", INTEGER, []);
-        let int = column("int", INT, []);
-        let tinyint = column("tinyint", TINYINT, []);
-        let smallint = column("smallint", SMALLINT, []);
-        let mediumint = column("mediumint", MEDIUMINT, []);
-        let bigint = column("bigint", BIGINT, []);
-        let unsigned_big_int = column("unsigned_big_int", UNSIGNED_BIG_INT, []);
-        let int2 = column("int2", INT2, []);
-        let int8 = column("int8", INT8, []);
-        let text = column("text", TEXT, []);
-        let clob = column("clob", CLOB, []);
-        let blob = column("blob", BLOB, []);
-        let real = column("real", REAL, []);
-        let double = column("double", DOUBLE, []);
-        let double_precision = column("double_precision", DOUBLE_PRECISION, []);
-        let float = column("float", FLOAT, []);
-        let numeric = column("numeric", NUMERIC, []);
-        let boolean = column("boolean", BOOLEAN, []);
-        let date = column("date", DATE, []);
-        let datetime = column("datetime", DATETIME, []);
-        let json = column("json", JSON, []);
+    fn structured_primary_key_and_unique() {
+        let col1 = column("col1", TEXT, [NOT_NULL]);
+        let col2 = column("col2", TEXT, [NOT_NULL]);
+        let table = TableDef::new(
+            "my_table",
+            [
+                col1.clone(),
+                col2.clone(),
+                primary_key([col1.clone(), col2.clone()]),
+                unique([col2.clone()]),
+            ],
+        );
+        assert_eq!(
+            "CREATE TABLE my_table (col1 TEXT NOT NULL, col2 TEXT NOT NULL, PRIMARY KEY (col1, col2), UNIQUE (col2))",
+            table.create_sql()
+        );
+        assert_eq!(vec!["col1", "col2"], table.primary_key_columns());
+        assert!(matches!(&*table.columns()[3], Column::Unique(keys) if keys == &["col2"]));
+
+        let inline = TableDef::new("my_table", [column("id", INTEGER, [PRIMARY_KEY]), col1]);
+        assert_eq!(vec!["id"], inline.primary_key_columns());
+
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&table.create_sql(), params![])
+            .unwrap();
+    }
 
+    #[test]
+    fn strict_for_version() {
         struct MyTable {
             columns: Vec<Arc<Column>>,
         }
@@ -369,41 +7821,177 @@ mod tests {
             fn columns(&self) -> &[Arc<Column>] {
                 &self.columns
             }
+
+            fn strict(&self) -> bool {
+                true
+            }
         }
 
-        let sql = MyTable {
-            columns: vec![
-                integer.clone(),
-                int.clone(),
-                tinyint.clone(),
-                smallint.clone(),
-                mediumint.clone(),
-                bigint.clone(),
-                unsigned_big_int.clone(),
-                int2.clone(),
-                int8.clone(),
-                text.clone(),
-                clob.clone(),
-                blob.clone(),
-                real.clone(),
-                double.clone(),
-                double_precision.clone(),
-                float.clone(),
-                numeric.clone(),
-                boolean.clone(),
-                date.clone(),
-                datetime.clone(),
-                json.clone(),
+        let table = MyTable {
+            columns: vec![column("id", INTEGER, [PRIMARY_KEY])],
+        };
+        assert_eq!(
+            Err(SchemaError::UnsupportedByVersion(
+                "STRICT requires 3.37.0".into()
+            )),
+            table.sql_for_version(SqliteVersion::new(3, 30, 0))
+        );
+
+        let sql = table.sql_for_version(SqliteVersion::new(3, 40, 0)).unwrap();
+        assert_eq!(table.create_sql(), sql);
+        rusqlite::Connection::open_in_memory()
+            .unwrap()
+            .execute(&sql, params![])
+            .unwrap();
+    }
+
+    #[test]
+    fn escape_string_keeps_backslashes() {
+        assert_eq!(r"'a\b'", escape_string(r"a\b"));
+        assert_eq!(r"'a\''b'", escape_string(r"a\'b"));
+
+        let table = TableDef::new(
+            "my_table",
+            [
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("val", TEXT, [NOT_NULL, DEFAULT(r"C:\dir\'s".into())]),
             ],
+        );
+        assert_eq!(
+            r"CREATE TABLE my_table (id INTEGER PRIMARY KEY, val TEXT NOT NULL DEFAULT 'C:\dir\''s')",
+            table.create_sql()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute("INSERT INTO my_table DEFAULT VALUES", params![])
+            .unwrap();
+        let val: String = conn
+            .query_row("SELECT val FROM my_table", params![], |row| row.get(0))
+            .unwrap();
+        assert_eq!(r"C:\dir\'s", val);
+    }
+
+    #[test]
+    fn append_create_sql_reused_buffer() {
+        let tables = (0..10)
+            .map(|index| {
+                TableDef::new(
+                    format!("table_{}", index),
+                    [
+                        column("id", INTEGER, [PRIMARY_KEY]),
+                        column(format!("val_{}", index), TEXT, []),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut buf = String::new();
+        for data in &tables {
+            data.append_create_sql(&mut buf);
+            buf.push_str(";\n");
         }
-        .create_sql();
+        let expected = tables
+            .iter()
+            .map(|data| format!("{};\n", data.create_sql()))
+            .collect::<String>();
+        assert_eq!(expected, buf);
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(&buf).unwrap();
+    }
 
+    #[test]
+    fn check_like_and_glob() {
+        let email = column("email", TEXT, [NOT_NULL]);
+        let slug = column("slug", TEXT, [NOT_NULL]);
+        let table = TableDef::new(
+            "my_table",
+            [
+                email.clone(),
+                slug.clone(),
+                check_like(&email, "%_@_%"),
+                check_glob(&slug, "[a-z]*[^A-Z' ]"),
+            ],
+        );
         assert_eq!(
-            sql,
-            format!("CREATE TABLE my_table ({} INTEGER, {} INT, {} TINYINT, {} SMALLINT, {} MEDIUMINT, {} BIGINT, {} UNSIGNED BIG INT, {} INT2, {} INT8, {} TEXT, {} CLOB, {} BLOB, {} REAL, {} DOUBLE, {} DOUBLE PRECISION, {} FLOAT, {} NUMERIC, {} BOOLEAN, {} DATE, {} DATETIME, {} JSON)",
-                    integer.name(), int.name(), tinyint.name(), smallint.name(), mediumint.name(), bigint.name(), unsigned_big_int.name(), int2.name(), int8.name(), text.name(), clob.name(), blob.name(), real.name(), double.name(), double_precision.name(), float.name(), numeric.name(), boolean.name(), date.name(), datetime.name(), json.name()
-            ));
+            r#"CREATE TABLE my_table (email TEXT NOT NULL, slug TEXT NOT NULL, CHECK ("email" LIKE '%_@_%'), CHECK ("slug" GLOB '[a-z]*[^A-Z'' ]'))"#,
+            table.create_sql()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        let insert = "INSERT INTO my_table (email, slug) VALUES (?, ?)";
+        conn.execute(insert, params!["a@example.com", "my-slug"])
+            .unwrap();
+        assert!(conn
+            .execute(insert, params!["example.com", "my-slug"])
+            .is_err());
+        assert!(conn
+            .execute(insert, params!["a@example.com", "My-Slug"])
+            .is_err());
+    }
+
+    #[test]
+    fn foreign_key_actions() {
+        let parent_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let parent = TableDef::new("parent_table", [parent_id.clone()]);
+        let child_parent_id = column("parent_id", INTEGER, []);
+        let fk = foreign_key_with_actions(
+            child_parent_id.clone(),
+            &parent,
+            parent_id,
+            &[
+                (Trigger::OnDelete, ReferentialAction::Cascade),
+                (Trigger::OnUpdate, ReferentialAction::SetNull),
+            ],
+        );
+        assert_eq!(
+            "FOREIGN KEY (parent_id) REFERENCES parent_table (id) ON DELETE CASCADE ON UPDATE SET NULL",
+            fk.create_statement()
+        );
+
+        let child = TableDef::new("child_table", [child_parent_id, fk]);
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        conn.execute(&parent.create_sql(), params![]).unwrap();
+        conn.execute(&child.create_sql(), params![]).unwrap();
+        conn.execute_batch(
+            "INSERT INTO parent_table (id) VALUES (1), (2);
+            INSERT INTO child_table (parent_id) VALUES (1), (2);
+            UPDATE parent_table SET id = 3 WHERE id = 2;
+            DELETE FROM parent_table WHERE id = 1;",
+        )
+        .unwrap();
+        let rows: Vec<Option<i64>> = conn
+            .prepare("SELECT parent_id FROM child_table")
+            .unwrap()
+            .query_map(params![], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(vec![None], rows);
+    }
 
+    #[test]
+    fn keys_first_create_sql() {
+        let a = column("a", TEXT, []);
+        let b = column("b", TEXT, []);
+        let table = TableDef::new(
+            "my_table",
+            [
+                a.clone(),
+                b.clone(),
+                column("id", INTEGER, [PRIMARY_KEY]),
+                unique([a, b]),
+                column("c", TEXT, []),
+            ],
+        );
+        let sql = table.create_sql_keys_first();
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, a TEXT, b TEXT, c TEXT, UNIQUE (a, b))",
+            sql
+        );
         rusqlite::Connection::open_in_memory()
             .unwrap()
             .execute(&sql, params![])
@@ -411,200 +7999,478 @@ mod tests {
     }
 
     #[test]
-    fn unique_constraint() {
-        struct UniqueTable {
-            columns: Vec<Arc<Column>>,
-        }
+    fn reindex_statement() {
+        assert_eq!("REINDEX", reindex_sql(None));
+        assert_eq!(
+            "REINDEX \"idx_users_email\"",
+            reindex_sql(Some("idx_users_email"))
+        );
 
-        impl Table for UniqueTable {
-            fn name(&self) -> &str {
-                "unique_table"
-            }
+        let email = column("email", TEXT, [COLLATE(CollationName::NOCASE)]);
+        let mut table = TableDef::new("users", [email.clone()]);
+        table.push_index("idx_users_email", [email]);
 
-            fn columns(&self) -> &[Arc<Column>] {
-                &self.columns
-            }
-        }
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute_batch(&table.create_index_terminated().join("\n"))
+            .unwrap();
+        conn.execute(&reindex_sql(Some("idx_users_email")), params![])
+            .unwrap();
 
-        let col1 = column("val1", TEXT, []);
-        let col2 = column("val2", TEXT, []);
-        let sql = UniqueTable {
-            columns: vec![
-                column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]),
-                col1.clone(),
-                col2.clone(),
-                unique([col1, col2]),
-            ],
+        #[cfg(feature = "rusqlite")]
+        {
+            reindex(&conn, Some("NOCASE")).unwrap();
+            reindex(&conn, None).unwrap();
         }
-        .create_sql();
+    }
 
-        assert_eq!(sql, "CREATE TABLE unique_table (id INTEGER PRIMARY KEY NOT NULL, val1 TEXT, val2 TEXT, UNIQUE (val1, val2))");
+    #[test]
+    fn decimal_scale() {
+        assert_eq!(DECIMAL(10, 0), Type::decimal(10));
+        assert_eq!("DECIMAL(10)", Type::decimal(10).name());
 
+        let my_table = table(
+            "my_table",
+            vec![
+                column("amount", Type::decimal(10), []),
+                column("price", DECIMAL(10, 2), []),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            "CREATE TABLE my_table (amount DECIMAL(10), price DECIMAL(10, 2))",
+            my_table.create_sql()
+        );
         rusqlite::Connection::open_in_memory()
             .unwrap()
-            .execute(&sql, params![])
+            .execute(&my_table.create_sql(), params![])
             .unwrap();
+
+        assert_eq!(
+            Some(SchemaError::InvalidDecimalScale("price".into())),
+            table("my_table", vec![column("price", DECIMAL(2, 5), [])]).err()
+        );
     }
 
     #[test]
-    fn primary_constraint() {
-        struct PrimaryTable {
-            column: Vec<Arc<Column>>,
-        }
+    fn nullable_column() {
+        assert_eq!(
+            "name TEXT NOT NULL",
+            column("name", TEXT, [])
+                .nullable(false)
+                .nullable(false)
+                .create_statement()
+        );
+        assert_eq!(
+            "name TEXT NOT NULL",
+            column("name", TEXT, [NOT_NULL])
+                .nullable(false)
+                .create_statement()
+        );
+        assert_eq!(
+            "name TEXT",
+            column("name", TEXT, []).nullable(true).create_statement()
+        );
 
-        impl Table for PrimaryTable {
-            fn name(&self) -> &str {
-                "primary_table"
-            }
+        let table = TableDef::new(
+            "my_table",
+            [
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("name", TEXT, []).nullable(false),
+                column("memo", TEXT, []).nullable(true),
+            ],
+        );
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute("INSERT INTO my_table (name) VALUES ('a')", params![])
+            .unwrap();
+        assert!(conn
+            .execute("INSERT INTO my_table (memo) VALUES ('a')", params![])
+            .is_err());
+    }
 
-            fn columns(&self) -> &[Arc<Column>] {
-                &self.column
-            }
-        }
+    #[test]
+    fn create_sql_header() {
+        let table = TableDef::new("my_table", [column("id", INTEGER, [PRIMARY_KEY])]);
+        let sql = table.create_sql_with_header();
+        let (header, rest) = sql.split_once('\n').unwrap();
+        assert_eq!("-- Generated by tinytable; do not edit.", header);
+        assert_eq!(table.create_sql(), rest);
+        assert!(!table.create_sql().starts_with("--"));
 
-        let col1 = column("col1", TEXT, []);
-        let col2 = column("col2", TEXT, []);
-        let sql = PrimaryTable {
-            column: vec![col1.clone(), col2.clone(), primary_key([col1, col2])],
-        }
-        .create_sql();
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(&sql).unwrap();
+    }
 
-        assert_eq!(
-            sql,
-            "CREATE TABLE primary_table (col1 TEXT, col2 TEXT, PRIMARY KEY (col1, col2))"
+    #[test]
+    fn table_name_eq() {
+        assert_eq!(TableName::from("users"), TableName::from("users"));
+        assert_ne!(TableName::from("users"), TableName::from("groups"));
+        assert_ne!(
+            TableName::from("users"),
+            TableName::from("users").alias("u")
         );
 
+        let name = TableName::from("users");
+        assert!(name == "users");
+        assert!(name == *"users");
+        assert!(TableName::from("users").alias("u") == "users");
+
+        let table = TableDef::new("users", [column("id", INTEGER, [PRIMARY_KEY])]);
+        assert_eq!(TableName::from(&table), name);
         rusqlite::Connection::open_in_memory()
             .unwrap()
-            .execute(&sql, params![])
+            .execute(&table.create_sql(), params![])
             .unwrap();
     }
 
+    #[cfg(feature = "rusqlite")]
     #[test]
-    fn foreignkey() {
-        struct MyTable {
-            hoge_column: Arc<Column>,
-            columns: Vec<Arc<Column>>,
+    fn sqlite_version_introspection() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let expected: String = conn
+            .query_row("SELECT sqlite_version()", params![], |row| row.get(0))
+            .unwrap();
+        let version = sqlite_version(&conn).unwrap();
+        assert_eq!(expected, version.to_string());
+        let (major, minor, patch) = version.into();
+        assert_eq!(SqliteVersion::new(major, minor, patch), version);
+
+        assert_eq!(Ok(SqliteVersion::new(3, 45, 1)), "3.45.1".parse());
+        for data in ["3.45", "3.45.1.2", "3.x.1", ""] {
+            assert_eq!(
+                Err(ParseSqliteVersionError(data.into())),
+                data.parse::<SqliteVersion>()
+            );
         }
 
-        impl MyTable {
-            fn new() -> Self {
-                let hoge_column = column("hoge", TEXT, []);
-                Self {
-                    hoge_column: hoge_column.clone(),
-                    columns: vec![
-                        column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]),
-                        column("val", TEXT, [DEFAULT("def".into())]),
-                        hoge_column.clone(),
-                    ],
-                }
-            }
+        assert_eq!(
+            version >= SqliteVersion::GENERATED_COLUMNS,
+            supports_generated_columns(&conn)
+        );
+        assert_eq!(version >= SqliteVersion::STRICT, supports_strict(&conn));
+        assert_eq!(
+            version >= SqliteVersion::RETURNING,
+            supports_returning(&conn)
+        );
+        assert_eq!(
+            version >= SqliteVersion::DROP_COLUMN,
+            supports_drop_column(&conn)
+        );
+
+        if supports_generated_columns(&conn) {
+            conn.execute_batch(
+                "CREATE TABLE my_table (a INTEGER);
+                ALTER TABLE my_table ADD COLUMN b INTEGER GENERATED ALWAYS AS (a * 2) VIRTUAL;",
+            )
+            .unwrap();
         }
+    }
 
-        impl Table for MyTable {
-            fn name(&self) -> &str {
-                "my_table"
-            }
+    #[test]
+    fn table_def_if_not_exists() {
+        let id = column("id", INTEGER, [PRIMARY_KEY]);
+        let name = column("name", TEXT, []);
+        let table = TableBuilder::new("my_table")
+            .column(id)
+            .column(name.clone())
+            .index("my_table_name", [name])
+            .if_not_exists()
+            .build();
+        assert_eq!(
+            vec![
+                "CREATE TABLE IF NOT EXISTS my_table (id INTEGER PRIMARY KEY, name TEXT)",
+                "CREATE INDEX IF NOT EXISTS my_table_name ON my_table (name)",
+            ],
+            table.create_all_sql()
+        );
 
-            fn columns(&self) -> &[Arc<Column>] {
-                &self.columns
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for _ in 0..2 {
+            for sql in table.create_all_sql() {
+                conn.execute(&sql, params![]).unwrap();
             }
         }
 
-        let my_table = MyTable::new();
+        let mut table = table;
+        table.set_if_not_exists(false);
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, name TEXT)",
+            table.cached_create_sql()
+        );
+        assert!(conn.execute(&table.create_sql(), params![]).is_err());
+    }
 
-        let mytable_sql = MyTable::new().create_sql();
+    #[test]
+    fn boolean_default() {
+        struct Keywords;
+
+        impl Dialect for Keywords {
+            fn boolean_keywords(&self) -> bool {
+                true
+            }
+        }
+
+        let table = TableDef::new(
+            "my_table",
+            [
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("enabled", BOOLEAN, [NOT_NULL, DEFAULT(true.into())]),
+                column("deleted", BOOLEAN, [NOT_NULL, DEFAULT(false.into())]),
+            ],
+        );
         assert_eq!(
-            mytable_sql,
-            "CREATE TABLE my_table (id INTEGER PRIMARY KEY NOT NULL, val TEXT DEFAULT 'def', hoge TEXT)"
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, enabled BOOLEAN NOT NULL DEFAULT 1, deleted BOOLEAN NOT NULL DEFAULT 0)",
+            table.create_sql()
+        );
+        assert_eq!(table.create_sql(), table.create_sql_for(&Sqlite));
+        let sql = table.create_sql_for(&Keywords);
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, enabled BOOLEAN NOT NULL DEFAULT TRUE, deleted BOOLEAN NOT NULL DEFAULT FALSE)",
+            sql
         );
 
-        rusqlite::Connection::open_in_memory()
-            .unwrap()
-            .execute(&mytable_sql, params![])
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&sql, params![]).unwrap();
+        conn.execute("INSERT INTO my_table DEFAULT VALUES", params![])
             .unwrap();
+        let row: (i64, i64) = conn
+            .query_row("SELECT enabled, deleted FROM my_table", params![], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((1, 0), row);
+    }
 
-        struct ForeignTable {
-            columns: Vec<Arc<Column>>,
-        }
+    #[test]
+    fn merge_table_defs() {
+        let id = column("id", INTEGER, [PRIMARY_KEY]);
+        let name = column("name", TEXT, [NOT_NULL]);
+        let mut base = TableDef::new("users", [id, name.clone()]);
+        base.push_index("users_name", [name]);
 
-        impl ForeignTable {
-            fn new(my_table: &MyTable) -> Self {
-                let rc_column = column("id", INTEGER, [PRIMARY_KEY, NOT_NULL]);
-                Self {
-                    columns: vec![
-                        rc_column.clone(),
-                        foreign_key(
-                            rc_column.clone(),
-                            REFERENCES,
-                            my_table,
-                            my_table.hoge_column.clone(),
-                            [],
-                        ),
-                    ],
-                }
-            }
+        let tenant_id = column("tenant_id", INTEGER, [NOT_NULL]);
+        let mut extension = TableDef::new("ignored", [tenant_id.clone(), check("tenant_id > 0")]);
+        extension.push_index("users_tenant_id", [tenant_id]);
+
+        let merged = base.merge(&extension).unwrap();
+        assert_eq!(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, tenant_id INTEGER NOT NULL, CHECK (tenant_id > 0))",
+            merged.create_sql()
+        );
+        assert_eq!(
+            vec![
+                "CREATE INDEX users_name ON users (name)",
+                "CREATE INDEX users_tenant_id ON users (tenant_id)",
+            ],
+            merged.create_index()
+        );
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for sql in merged.create_all_sql() {
+            conn.execute(&sql, params![]).unwrap();
         }
 
-        impl Table for ForeignTable {
-            fn name(&self) -> &str {
-                "foreign_table"
-            }
+        let colliding = TableDef::new("ignored", [column("Name", TEXT, [])]);
+        assert_eq!(
+            Some(SchemaError::DuplicateColumn("Name".into())),
+            base.merge(&colliding).err()
+        );
+    }
 
-            fn columns(&self) -> &[Arc<Column>] {
-                &self.columns
-            }
+    #[test]
+    fn rust_to_sql_expr() {
+        assert_eq!(
+            "if self.flag { 1 } else { 0 }",
+            BOOLEAN.rust_to_sql_expr("flag")
+        );
+        assert_eq!("self.id", INTEGER.rust_to_sql_expr("id"));
+        assert_eq!("self.created_at", DATETIME.rust_to_sql_expr("created_at"));
+        assert_eq!("self.name", TEXT.rust_to_sql_expr("name"));
+        assert_eq!("self.score", REAL.rust_to_sql_expr("score"));
+        assert_eq!("self.data", BLOB.rust_to_sql_expr("data"));
+        assert_eq!(
+            "self.shape",
+            Raw("GEOMETRY".into()).rust_to_sql_expr("shape")
+        );
+        assert_eq!(
+            "self.uuid",
+            Declared("UUID".into(), Affinity::TEXT).rust_to_sql_expr("uuid")
+        );
+        assert_eq!(
+            "self.flag",
+            Declared("BOOLEAN".into(), Affinity::NUMERIC).rust_to_sql_expr("flag")
+        );
+    }
+
+    #[test]
+    fn date_column_check() {
+        let table = TableDef::new(
+            "my_table",
+            [
+                column("id", INTEGER, [PRIMARY_KEY]),
+                date_column("birthday", [NOT_NULL]),
+            ],
+        );
+        assert_eq!(
+            "CREATE TABLE my_table (id INTEGER PRIMARY KEY, birthday DATE NOT NULL CHECK (birthday GLOB '[0-9][0-9][0-9][0-9]-[0-1][0-9]-[0-3][0-9]' AND substr(birthday, 6, 2) BETWEEN '01' AND '12' AND substr(birthday, 9, 2) BETWEEN '01' AND '31'))",
+            table.create_sql()
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        let insert = "INSERT INTO my_table (birthday) VALUES (?)";
+        conn.execute(insert, params!["2020-01-31"]).unwrap();
+        for data in [
+            "2020-13-40",
+            "2020-13-01",
+            "2020-00-10",
+            "20-01-01",
+            "garbage",
+        ] {
+            assert!(conn.execute(insert, params![data]).is_err(), "{}", data);
         }
+    }
 
-        let foreigntable_sql = ForeignTable::new(&my_table).create_sql();
+    #[test]
+    fn diff_table_columns() {
+        let current = TableDef::new(
+            "users",
+            [
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("name", TEXT, []),
+                column("age", TEXT, []),
+                column("legacy", BLOB, []),
+            ],
+        );
+        let desired = TableDef::new(
+            "users",
+            [
+                column("id", INTEGER, [PRIMARY_KEY]),
+                column("Name", TEXT, [NOT_NULL]),
+                column("age", INTEGER, []),
+                column("email", TEXT, []),
+                unique([column("email", TEXT, [])]),
+            ],
+        );
         assert_eq!(
-            foreigntable_sql,
-            "CREATE TABLE foreign_table (id INTEGER PRIMARY KEY NOT NULL, FOREIGN KEY (id) REFERENCES my_table (hoge))");
+            ColumnDiff {
+                added: vec!["email".into()],
+                removed: vec!["legacy".into()],
+                type_changed: vec![("age".into(), TEXT, INTEGER)],
+            },
+            diff_columns(&current, &desired)
+        );
+        assert_eq!(ColumnDiff::default(), diff_columns(&current, &current));
 
-        rusqlite::Connection::open_in_memory()
-            .unwrap()
-            .execute(&foreigntable_sql, params![])
-            .unwrap();
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&desired.create_sql(), params![]).unwrap();
     }
 
     #[test]
-    fn index() {
-        struct MyTable {
-            columns: Vec<Arc<Column>>,
-            indexes: Vec<(String, Vec<Arc<Column>>)>,
-        }
+    fn try_foreign_key_keyword() {
+        let parent_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let child_parent_id = column("parent_id", INTEGER, []);
+        assert_eq!(
+            Some(SchemaError::InvalidForeignKey("ON DELETE".into())),
+            try_foreign_key(
+                child_parent_id.clone(),
+                #[allow(deprecated)]
+                ON_DELETE,
+                "parent_table",
+                parent_id.clone(),
+                []
+            )
+            .err()
+        );
 
-        impl Table for MyTable {
-            fn name(&self) -> &str {
-                "my_table"
-            }
+        let fk = try_foreign_key(
+            child_parent_id.clone(),
+            REFERENCES,
+            "parent_table",
+            parent_id,
+            [],
+        )
+        .unwrap();
+        assert_eq!(
+            "FOREIGN KEY (parent_id) REFERENCES parent_table (id)",
+            fk.create_statement()
+        );
 
-            fn columns(&self) -> &[Arc<Column>] {
-                &self.columns
-            }
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE parent_table (id INTEGER PRIMARY KEY);")
+            .unwrap();
+        conn.execute(
+            &TableDef::new("child_table", [child_parent_id, fk]).create_sql(),
+            params![],
+        )
+        .unwrap();
+    }
 
-            fn indexes(&self) -> &[(String, Vec<Arc<Column>>)] {
-                &self.indexes
-            }
+    #[test]
+    fn standalone_referential_action() {
+        let parent_id = column("id", INTEGER, [PRIMARY_KEY]);
+        let child_parent_id = column("parent_id", INTEGER, []);
+        #[allow(deprecated)]
+        let attributes = [
+            vec![SET_NULL],
+            vec![ON_DELETE],
+            vec![ON_UPDATE, DEFERRABLE_INITIALLY_DEFERRED],
+        ];
+        for data in attributes {
+            assert!(matches!(
+                try_foreign_key(
+                    child_parent_id.clone(),
+                    REFERENCES,
+                    "parent_table",
+                    parent_id.clone(),
+                    data
+                ),
+                Err(SchemaError::InvalidForeignKey(_))
+            ));
         }
 
-        let col1 = column("col1", INTEGER, [PRIMARY_KEY, NOT_NULL]);
-        let col2 = column("col2", TEXT, [NOT_NULL]);
-        let col3 = column("col3", TEXT, [NOT_NULL]);
-        let table = MyTable {
-            columns: vec![col1, col2.clone(), col3.clone()],
-            indexes: vec![
-                ("col2_index".into(), vec![col2.clone()]),
-                ("col2_3_index".into(), vec![col2, col3]),
-            ],
-        };
+        #[allow(deprecated)]
+        let inline = references("parent_table", parent_id, [ON_UPDATE, CASCADE]);
+        assert_eq!(
+            INLINE_REFERENCES("parent_table (id) ON UPDATE CASCADE".into()),
+            inline
+        );
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE parent_table (id INTEGER PRIMARY KEY);")
+            .unwrap();
+        conn.execute(
+            &TableDef::new("child_table", [column("parent_id", INTEGER, [inline])]).create_sql(),
+            params![],
+        )
+        .unwrap();
+    }
 
-        let indexes = table.create_index();
-        assert_eq!(2, indexes.len());
-        assert_eq!("CREATE INDEX col2_index ON my_table (col2)", indexes[0]);
+    #[test]
+    #[should_panic]
+    fn references_standalone_action() {
+        #[allow(deprecated)]
+        references("parent_table", column("id", INTEGER, []), [CASCADE]);
+    }
+
+    #[test]
+    fn index_def_matches_table_index() {
+        let first = column("first", TEXT, []);
+        let last = column("last", TEXT, []);
+        let mut table = TableDef::new("people", [first.clone(), last.clone()]);
+        table
+            .push_index("people_name", [first.clone(), last.clone()])
+            .set_if_not_exists(true);
+
+        let index = IndexDef::new("people_name", &table, [first, last]).if_not_exists();
         assert_eq!(
-            "CREATE INDEX col2_3_index ON my_table (col2,col3)",
-            indexes[1]
+            "CREATE INDEX IF NOT EXISTS people_name ON people (first,last)",
+            index.create_sql()
         );
+        assert_eq!(vec![index.create_sql()], table.create_index());
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(&table.create_sql(), params![]).unwrap();
+        conn.execute(&index.create_sql(), params![]).unwrap();
+        conn.execute(&index.create_sql(), params![]).unwrap();
     }
 }